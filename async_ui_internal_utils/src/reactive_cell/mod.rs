@@ -9,6 +9,7 @@ use std::{
 use crate::wakers_arena::{WakersArena, WakersSublist};
 
 use self::borrow_mut::ReactiveCellBorrowMut;
+pub use self::until_change::UntilChangeFuture;
 
 /// Like a [RefCell], but you can subscribe to changes.
 ///
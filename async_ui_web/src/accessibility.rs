@@ -0,0 +1,260 @@
+/*!
+Accessibility helpers.
+
+### Announcements
+Screen readers read out changes to
+[live regions](https://developer.mozilla.org/en-US/docs/Web/Accessibility/ARIA/ARIA_Live_Regions).
+The [announce] function puts a message in a live region shared by the whole app,
+so that updates that aren't otherwise visible to screen reader users
+(such as "Saved" or "3 results") get read out.
+
+```
+# use async_ui_web::accessibility::announce;
+# let _ = async {
+// ...after saving...
+announce("Saved");
+# };
+```
+
+The live regions are created the first time they are needed, and appended to
+the `<body>` of the document (outside of your app's own elements).
+They are visually hidden.
+
+### Relationships
+The [ShortcutAria] trait links elements together with
+`aria-labelledby` and `aria-describedby`, generating ids where needed.
+
+```
+# use async_ui_web::{html::{Input, Span}, join, prelude_traits::*};
+# let _ = async {
+let input = Input::new();
+let hint = Span::new();
+input.add_aria_describedby(&hint);
+join((input.render(), hint.render("At least 8 characters".render()))).await;
+# };
+```
+*/
+
+use std::{
+    cell::{Cell, RefCell},
+    time::Duration,
+};
+
+use async_executor::Task;
+use async_ui_web_core::{executor::schedule, window::DOCUMENT};
+use gloo_timers::future::TimeoutFuture;
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::{Element, HtmlElement};
+
+use crate::{
+    executor::get_executor,
+    style::{ShortcutStyle, Styles},
+};
+
+/// How long to wait between clearing a live region and filling it.
+/// Screen readers don't notice a change that is undone in the same frame,
+/// so without this, repeating a message would be silent.
+const ANNOUNCE_DELAY: Duration = Duration::from_millis(100);
+/// How long a message stays in the live region. Clearing it afterwards stops
+/// stale messages from being read when the user browses through the page.
+const CLEAR_DELAY: Duration = Duration::from_secs(5);
+/// Prefix for the ids generated by [ShortcutAria::aria_id].
+const ID_PREFIX: &str = "async-ui-id-";
+
+thread_local! {
+    static REGIONS: RefCell<[Option<LiveRegion>; 2]> = const { RefCell::new([None, None]) };
+    static NEXT_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// How urgently a screen reader should read an announcement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Politeness {
+    /// Read once the user is idle. This is right for most messages.
+    Polite,
+    /// Interrupt whatever is being read. Only use this for urgent messages,
+    /// such as errors.
+    Assertive,
+}
+
+impl Politeness {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Polite => "polite",
+            Self::Assertive => "assertive",
+        }
+    }
+}
+
+/// Have screen readers read out the given message, politely.
+///
+/// Announcing the same message twice reads it twice.
+/// A message replaces any previous message that hasn't been read yet.
+///
+/// See the [module-level documentation][self] for more.
+pub fn announce(message: &str) {
+    announce_with(message, Politeness::Polite);
+}
+
+/// Have screen readers read out the given message, with the given politeness.
+pub fn announce_with(message: &str, politeness: Politeness) {
+    REGIONS.with(|regions| {
+        let mut regions = regions.borrow_mut();
+        let region =
+            regions[politeness as usize].get_or_insert_with(|| LiveRegion::new(politeness));
+        region.announce(message);
+    });
+}
+
+/// Styles that hide an element visually while keeping it available to
+/// screen readers.
+///
+/// This is the common "visually hidden" (or "sr-only") pattern.
+pub fn visually_hidden() -> Styles {
+    Styles::new()
+        .property("position", "absolute")
+        .property("width", "1px")
+        .property("height", "1px")
+        .property("margin", "-1px")
+        .property("padding", "0")
+        .property("border", "0")
+        .property("overflow", "hidden")
+        .property("clip", "rect(0 0 0 0)")
+        .property("white-space", "nowrap")
+}
+
+struct LiveRegion {
+    element: HtmlElement,
+    /// Fills and then clears the region.
+    /// Replacing it cancels the previous announcement.
+    task: Option<Task<()>>,
+}
+
+impl LiveRegion {
+    fn new(politeness: Politeness) -> Self {
+        let element: HtmlElement =
+            DOCUMENT.with(|doc| doc.create_element("div").unwrap_throw().unchecked_into());
+        element
+            .set_attribute("aria-live", politeness.as_str())
+            .unwrap_throw();
+        element.set_attribute("aria-atomic", "true").unwrap_throw();
+        element.set_styles(&visually_hidden());
+        Self {
+            element,
+            task: None,
+        }
+    }
+    fn announce(&mut self, message: &str) {
+        // the app may have replaced the body
+        if !self.element.is_connected() {
+            DOCUMENT.with(|doc| {
+                doc.body()
+                    .unwrap_throw()
+                    .append_child(&self.element)
+                    .unwrap_throw()
+            });
+        }
+        self.element.set_text_content(None);
+        let element = self.element.clone();
+        let message = message.to_owned();
+        self.task = Some(get_executor().spawn(async move {
+            TimeoutFuture::new(ANNOUNCE_DELAY.as_millis() as u32).await;
+            element.set_text_content(Some(&message));
+            TimeoutFuture::new(CLEAR_DELAY.as_millis() as u32).await;
+            element.set_text_content(None);
+        }));
+        schedule();
+    }
+}
+
+/// Link elements with ARIA relationship attributes.
+///
+/// This is implemented for everything that is an [Element],
+/// and is included in the [prelude_traits][crate::prelude_traits].
+pub trait ShortcutAria: AsRef<Element> {
+    /// Get the `id` of this element, generating a unique one if it has none.
+    fn aria_id(&self) -> String {
+        let element = self.as_ref();
+        let id = element.id();
+        if !id.is_empty() {
+            return id;
+        }
+        let id = format!(
+            "{ID_PREFIX}{}",
+            NEXT_ID.with(|next| next.replace(next.get() + 1))
+        );
+        element.set_id(&id);
+        id
+    }
+    /// Add the given element to the `aria-labelledby` of this element.
+    fn add_aria_labelledby(&self, label: &impl AsRef<Element>) {
+        add_reference(self.as_ref(), "aria-labelledby", label.as_ref());
+    }
+    /// Remove the given element from the `aria-labelledby` of this element.
+    fn remove_aria_labelledby(&self, label: &impl AsRef<Element>) {
+        remove_reference(self.as_ref(), "aria-labelledby", label.as_ref());
+    }
+    /// Add the given element to the `aria-describedby` of this element.
+    fn add_aria_describedby(&self, description: &impl AsRef<Element>) {
+        add_reference(self.as_ref(), "aria-describedby", description.as_ref());
+    }
+    /// Remove the given element from the `aria-describedby` of this element.
+    fn remove_aria_describedby(&self, description: &impl AsRef<Element>) {
+        remove_reference(self.as_ref(), "aria-describedby", description.as_ref());
+    }
+}
+
+impl<T: AsRef<Element>> ShortcutAria for T {}
+
+fn add_reference(element: &Element, attribute: &str, target: &Element) {
+    let id = target.aria_id();
+    let current = element.get_attribute(attribute).unwrap_or_default();
+    element
+        .set_attribute(attribute, &add_token(&current, &id))
+        .unwrap_throw();
+}
+
+fn remove_reference(element: &Element, attribute: &str, target: &Element) {
+    let id = target.id();
+    let Some(current) = element.get_attribute(attribute) else {
+        return;
+    };
+    let new = remove_token(&current, &id);
+    if new.is_empty() {
+        element.remove_attribute(attribute).unwrap_throw();
+    } else {
+        element.set_attribute(attribute, &new).unwrap_throw();
+    }
+}
+
+/// Add a token to a space-separated list, if it isn't there already.
+fn add_token(list: &str, token: &str) -> String {
+    if list.split_ascii_whitespace().any(|t| t == token) {
+        return list.to_owned();
+    }
+    let mut tokens: Vec<&str> = list.split_ascii_whitespace().collect();
+    tokens.push(token);
+    tokens.join(" ")
+}
+
+/// Remove a token from a space-separated list.
+fn remove_token(list: &str, token: &str) -> String {
+    list.split_ascii_whitespace()
+        .filter(|&t| t != token)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_lists() {
+        assert_eq!(add_token("", "a"), "a");
+        assert_eq!(add_token("a b", "c"), "a b c");
+        assert_eq!(add_token("a b", "b"), "a b");
+        assert_eq!(remove_token("a b c", "b"), "a c");
+        assert_eq!(remove_token(" a ", "a"), "");
+        assert_eq!(remove_token("a", "b"), "a");
+    }
+}
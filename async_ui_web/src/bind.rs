@@ -0,0 +1,314 @@
+/*!
+Two-way bindings between form elements and state.
+
+Each function here returns a Future that keeps an element and a piece of
+[ReactiveMut] state (such as a [ReactiveCell][crate::ReactiveCell]) in sync
+for as long as it is running.
+When the user edits the element, the state is updated.
+When the state is changed by your code, the element is updated.
+
+```
+# use async_ui_web::{bind, html::Input, join, ReactiveCell};
+# let _ = async {
+let name = ReactiveCell::new(String::from("Ferris"));
+let input = Input::new_text();
+join((input.render(), bind::text(&input, &name))).await;
+# };
+```
+
+Updates in one direction never bounce back in the other:
+the element is only written to if its current content doesn't already
+represent the state value, and the state is only written to if the element
+content is different from the current value.
+This means the text the user is typing (say, `1.` in a number input bound to an `f64`)
+is never reformatted under their cursor.
+*/
+
+use std::{cell::RefCell, str::FromStr, task::Poll};
+
+use async_ui_web_html::nodes::{Input, Select, TextArea};
+use futures_lite::{Stream, StreamExt};
+use web_sys::{HtmlElement, HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+
+use crate::{event_traits::EmitHtmlElementEvent, race, reactive::ReactiveMut};
+
+/// Elements with a string `value` that the user can edit:
+/// `<input>`, `<textarea>`, and `<select>`.
+pub trait ValueElement: AsRef<HtmlElement> {
+    /// Get the `value` of the element.
+    fn element_value(&self) -> String;
+    /// Set the `value` of the element.
+    fn set_element_value(&self, value: &str);
+    /// Set the custom validity message of the element.
+    /// An empty string marks the element as valid.
+    ///
+    /// See [the MDN docs](https://developer.mozilla.org/en-US/docs/Web/API/HTMLObjectElement/setCustomValidity).
+    fn set_element_validity(&self, message: &str);
+}
+
+macro_rules! impl_value_element {
+    ($elem:ty, $node:ty) => {
+        impl ValueElement for $elem {
+            fn element_value(&self) -> String {
+                self.value()
+            }
+            fn set_element_value(&self, value: &str) {
+                self.set_value(value)
+            }
+            fn set_element_validity(&self, message: &str) {
+                self.set_custom_validity(message)
+            }
+        }
+        impl ValueElement for $node {
+            fn element_value(&self) -> String {
+                self.element.value()
+            }
+            fn set_element_value(&self, value: &str) {
+                self.element.set_value(value)
+            }
+            fn set_element_validity(&self, message: &str) {
+                self.element.set_custom_validity(message)
+            }
+        }
+    };
+}
+impl_value_element!(HtmlInputElement, Input);
+impl_value_element!(HtmlTextAreaElement, TextArea);
+impl_value_element!(HtmlSelectElement, Select);
+
+/// Keep the state in sync with the element, in both directions.
+///
+/// * `write_element` is called with the state value when the state changes.
+///   It should check whether the element already shows the value before writing.
+/// * `read_element` is called when the element fires one of `element_changes`.
+///   It should return `None` if the element content can't be converted to a value.
+async fn bind_with<S>(
+    state: &S,
+    mut write_element: impl FnMut(&S::Value),
+    mut element_changes: impl Stream + Unpin,
+    mut read_element: impl FnMut() -> Option<S::Value>,
+) where
+    S: ReactiveMut + ?Sized,
+    S::Value: PartialEq,
+{
+    state.with_value(&mut write_element);
+    let mut state_changes = state.until_value_change();
+    race((
+        async {
+            while state_changes.next().await.is_some() {
+                state.with_value(&mut write_element);
+            }
+        },
+        async {
+            while element_changes.next().await.is_some() {
+                if let Some(value) = read_element() {
+                    if state.with_value(|old| old != &value).unwrap_or(true) {
+                        state.set_value(value);
+                    }
+                }
+            }
+        },
+    ))
+    .await
+}
+
+/// Bind the `value` of an `<input>`, `<textarea>`, or `<select>` to a [String].
+///
+/// The state is updated on every `input` event.
+pub async fn text<S>(element: &impl ValueElement, state: &S)
+where
+    S: ReactiveMut<Value = String> + ?Sized,
+{
+    bind_with(
+        state,
+        |value| {
+            if element.element_value() != *value {
+                element.set_element_value(value);
+            }
+        },
+        element.as_ref().until_input(),
+        || Some(element.element_value()),
+    )
+    .await
+}
+
+/// Bind the `value` of an element (usually a number or range `<input>`) to a
+/// value parsed with [FromStr], such as an integer or a float.
+///
+/// The state is updated on every `input` event where the content parses
+/// successfully. Leading and trailing whitespace is ignored.
+///
+/// `on_error` is called with the parse error when the content fails to parse,
+/// and with `None` once the content is valid again (or when the element is
+/// overwritten because the state changed).
+/// You can use it to show a message, or to call
+/// [set_custom_validity][web_sys::HtmlInputElement::set_custom_validity].
+///
+/// ```
+/// # use async_ui_web::{bind, html::Input, join, ReactiveCell};
+/// # let _ = async {
+/// let age = ReactiveCell::new(30u8);
+/// let input = Input::new_number();
+/// join((
+///     input.render(),
+///     bind::parsed(&input, &age, |error| {
+///         let message = error.map(|e| e.to_string()).unwrap_or_default();
+///         input.set_custom_validity(&message);
+///     }),
+/// ))
+/// .await;
+/// # };
+/// ```
+pub async fn parsed<S, T>(
+    element: &impl ValueElement,
+    state: &S,
+    on_error: impl FnMut(Option<&T::Err>),
+) where
+    S: ReactiveMut<Value = T> + ?Sized,
+    T: FromStr + ToString + PartialEq,
+{
+    let on_error = RefCell::new(on_error);
+    bind_with(
+        state,
+        |value| {
+            if element.element_value().trim().parse().ok().as_ref() != Some(value) {
+                element.set_element_value(&value.to_string());
+                (on_error.borrow_mut())(None);
+            }
+        },
+        element.as_ref().until_input(),
+        || {
+            let parsed = element.element_value().trim().parse();
+            (on_error.borrow_mut())(parsed.as_ref().err());
+            parsed.ok()
+        },
+    )
+    .await
+}
+
+/// Bind the `checked` state of a checkbox `<input>` to a [bool].
+///
+/// The state is updated on every `change` event.
+pub async fn checked<S>(input: &impl AsRef<HtmlInputElement>, state: &S)
+where
+    S: ReactiveMut<Value = bool> + ?Sized,
+{
+    let input = input.as_ref();
+    bind_with(
+        state,
+        |&value| {
+            if input.checked() != value {
+                input.set_checked(value);
+            }
+        },
+        input.until_change(),
+        || Some(input.checked()),
+    )
+    .await
+}
+
+/// Bind a group of radio `<input>`s to a value (usually an enum).
+///
+/// Each input is paired with the value it represents.
+/// When the user checks an input, the state is set to its value.
+/// When the state changes, the matching input is checked
+/// (or all are unchecked if none match).
+///
+/// The inputs should share the same `name` attribute so that the browser
+/// unchecks the others.
+///
+/// ```
+/// # use async_ui_web::{bind, html::Input, join, ReactiveCell};
+/// # let _ = async {
+/// #[derive(Clone, PartialEq)]
+/// enum Size { Small, Large }
+/// let size = ReactiveCell::new(Size::Small);
+/// let (small, large) = (Input::new_radio(), Input::new_radio());
+/// small.set_name("size");
+/// large.set_name("size");
+/// join((
+///     small.render(),
+///     large.render(),
+///     bind::radio_group(&[(&small, Size::Small), (&large, Size::Large)], &size),
+/// ))
+/// .await;
+/// # };
+/// ```
+pub async fn radio_group<S, T, I>(options: &[(&I, T)], state: &S)
+where
+    S: ReactiveMut<Value = T> + ?Sized,
+    T: PartialEq + Clone,
+    I: AsRef<HtmlInputElement> + ?Sized,
+{
+    let mut changes = options
+        .iter()
+        .map(|(input, _)| input.as_ref().until_change())
+        .collect::<Vec<_>>();
+    let changes = futures_lite::stream::poll_fn(|cx| {
+        // poll every stream so that all of them have their listeners attached
+        let mut fired = false;
+        for stream in changes.iter_mut() {
+            fired |= stream.poll_next(cx).is_ready();
+        }
+        if fired {
+            Poll::Ready(Some(()))
+        } else {
+            Poll::Pending
+        }
+    });
+    bind_with(
+        state,
+        |value| {
+            for (input, option) in options {
+                let input = input.as_ref();
+                let checked = option == value;
+                if input.checked() != checked {
+                    input.set_checked(checked);
+                }
+            }
+        },
+        changes,
+        || {
+            options
+                .iter()
+                .find(|(input, _)| input.as_ref().checked())
+                .map(|(_, option)| option.clone())
+        },
+    )
+    .await
+}
+
+/// Bind a date or time `<input>` to a timestamp in milliseconds.
+///
+/// This uses the [valueAsNumber](https://developer.mozilla.org/en-US/docs/Web/API/HTMLInputElement/valueAsNumber)
+/// property, so the meaning of the number depends on the input type:
+///
+/// * for `date` and `datetime-local`, it is milliseconds since the UNIX epoch (in UTC),
+/// * for `time`, it is milliseconds since midnight,
+/// * for `month`, it is the number of months since January 1970.
+///
+/// An empty input corresponds to `None`.
+///
+/// If you'd rather work with the raw strings (like `2023-08-31`),
+/// use [text] instead.
+pub async fn date_time<S>(input: &impl AsRef<HtmlInputElement>, state: &S)
+where
+    S: ReactiveMut<Value = Option<f64>> + ?Sized,
+{
+    let input = input.as_ref();
+    let read = || Some(input.value_as_number()).filter(|v| !v.is_nan());
+    bind_with(
+        state,
+        |&value| {
+            if read() != value {
+                match value {
+                    Some(value) => input.set_value_as_number(value),
+                    None => input.set_value(""),
+                }
+            }
+        },
+        input.until_input(),
+        || Some(read()),
+    )
+    .await
+}
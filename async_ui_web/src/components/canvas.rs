@@ -0,0 +1,233 @@
+use std::{
+    cell::{Cell, RefCell},
+    future::{pending, Future},
+    ops::Deref,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use async_ui_web_core::window::{DOCUMENT, WINDOW};
+use async_ui_web_html::{events::EmitEvent, nodes::Canvas};
+use futures_lite::StreamExt;
+use wasm_bindgen::{prelude::Closure, JsCast, UnwrapThrowExt};
+use web_sys::CanvasRenderingContext2d;
+
+use crate::{join, observers::ObserveElement, race, NoChild};
+
+/**
+A `<canvas>` with a 2D context that stays sharp on high-DPI screens.
+
+The canvas is sized with CSS (for example, `width: 100%; height: 300px;`).
+Its backing store is kept at that size multiplied by the
+[device pixel ratio](https://developer.mozilla.org/en-US/docs/Web/API/Window/devicePixelRatio),
+and the context is scaled so that you can draw in CSS pixels.
+
+```
+# use async_ui_web::{components::Canvas2d, join, style::ShortcutStyle};
+# let _ = async {
+let canvas = Canvas2d::new();
+canvas.set_style("width", "100%");
+canvas.set_style("height", "300px");
+join((
+    canvas.render(),
+    canvas.draw_loop(|ctx, time| {
+        let (width, height) = canvas.size();
+        ctx.clear_rect(0.0, 0.0, width, height);
+        let x = (time / 10.0) % width;
+        ctx.fill_rect(x, height / 2.0 - 10.0, 20.0, 20.0);
+    }),
+))
+.await;
+# };
+```
+
+This type [Deref]s to [Canvas], so you can use all the HTML methods on it.
+*/
+pub struct Canvas2d {
+    canvas: Canvas,
+    context: CanvasRenderingContext2d,
+    /// Size in CSS pixels, and the pixel ratio the backing store was made for.
+    size: Cell<(f64, f64, f64)>,
+}
+
+impl Default for Canvas2d {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for Canvas2d {
+    type Target = Canvas;
+    fn deref(&self) -> &Self::Target {
+        &self.canvas
+    }
+}
+
+impl Canvas2d {
+    /// Create a new canvas.
+    pub fn new() -> Self {
+        let canvas = Canvas::new();
+        let context = canvas
+            .get_context("2d")
+            .unwrap_throw()
+            .unwrap_throw()
+            .unchecked_into();
+        Self {
+            canvas,
+            context,
+            size: Cell::new((0.0, 0.0, 0.0)),
+        }
+    }
+    /// The 2D rendering context, scaled so that one unit is one CSS pixel.
+    pub fn context(&self) -> &CanvasRenderingContext2d {
+        &self.context
+    }
+    /// The size of the canvas on the page, in CSS pixels.
+    pub fn size(&self) -> (f64, f64) {
+        let (width, height, _) = self.size.get();
+        (width, height)
+    }
+    /// Put the canvas on the screen, and keep its backing store sized to it.
+    ///
+    /// This async method never completes.
+    pub async fn render(&self) {
+        join((self.canvas.render(NoChild), async {
+            let mut resizes = self.canvas.until_resize();
+            while let Some(entry) = resizes.next().await {
+                let rect = entry.content_rect();
+                self.sync_size(rect.width(), rect.height());
+            }
+        }))
+        .await;
+    }
+    /// Call `draw` on every animation frame, with the context and the frame
+    /// time (in milliseconds, from
+    /// [requestAnimationFrame](https://developer.mozilla.org/en-US/docs/Web/API/window/requestAnimationFrame)).
+    ///
+    /// The loop pauses while the canvas is scrolled out of view or
+    /// the tab is hidden.
+    ///
+    /// Resizing the canvas clears it, so `draw` should redraw everything.
+    ///
+    /// This async method never completes.
+    pub async fn draw_loop(&self, mut draw: impl FnMut(&CanvasRenderingContext2d, f64)) {
+        enum Wake {
+            Frame(f64),
+            Visible(bool),
+            TabVisibilityChanged,
+        }
+        let mut intersections = self.canvas.until_intersection(&Default::default());
+        let mut visibility_changes =
+            DOCUMENT.with(|doc| doc.until_event::<web_sys::Event>("visibilitychange".into()));
+        let mut visible = true;
+        loop {
+            let paused = !visible || DOCUMENT.with(|doc| doc.hidden());
+            let wake = race((
+                async {
+                    if paused {
+                        pending().await
+                    } else {
+                        Wake::Frame(AnimationFrame::new().await)
+                    }
+                },
+                async {
+                    match intersections.next().await {
+                        Some(entry) => Wake::Visible(entry.is_intersecting()),
+                        None => pending().await,
+                    }
+                },
+                async {
+                    (&mut visibility_changes).await;
+                    Wake::TabVisibilityChanged
+                },
+            ))
+            .await;
+            match wake {
+                Wake::Frame(time) => {
+                    // the pixel ratio changes with zoom and when moving between screens
+                    let (width, height, _) = self.size.get();
+                    self.sync_size(width, height);
+                    draw(&self.context, time);
+                }
+                Wake::Visible(is_visible) => visible = is_visible,
+                Wake::TabVisibilityChanged => {}
+            }
+        }
+    }
+    fn sync_size(&self, width: f64, height: f64) {
+        let ratio = WINDOW.with(|window| window.device_pixel_ratio());
+        if self.size.replace((width, height, ratio)) == (width, height, ratio) {
+            return;
+        }
+        self.canvas.set_width((width * ratio).round() as u32);
+        self.canvas.set_height((height * ratio).round() as u32);
+        // resizing resets the context state, including the transform
+        self.context
+            .set_transform(ratio, 0.0, 0.0, ratio, 0.0, 0.0)
+            .unwrap_throw();
+    }
+}
+
+/// A Future that resolves with the timestamp of the next animation frame.
+///
+/// The frame request is cancelled if this is dropped first.
+pub(crate) struct AnimationFrame {
+    id: i32,
+    shared: Rc<RefCell<(Option<f64>, Option<Waker>)>>,
+    _closure: Closure<dyn FnMut(f64)>,
+}
+
+impl AnimationFrame {
+    pub(crate) fn new() -> Self {
+        let shared: Rc<RefCell<(Option<f64>, Option<Waker>)>> = Default::default();
+        let closure = Closure::<dyn FnMut(f64)>::new({
+            let shared = shared.clone();
+            move |time| {
+                let waker = {
+                    let mut shared = shared.borrow_mut();
+                    shared.0 = Some(time);
+                    shared.1.take()
+                };
+                if let Some(waker) = waker {
+                    waker.wake();
+                }
+                // run inside this callback, so that drawing happens in this frame
+                async_ui_web_core::executor::run_now();
+            }
+        });
+        let id = WINDOW.with(|window| {
+            window
+                .request_animation_frame(closure.as_ref().unchecked_ref())
+                .unwrap_throw()
+        });
+        Self {
+            id,
+            shared,
+            _closure: closure,
+        }
+    }
+}
+
+impl Future for AnimationFrame {
+    type Output = f64;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut shared = self.shared.borrow_mut();
+        match shared.0 {
+            Some(time) => Poll::Ready(time),
+            None => {
+                shared.1 = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for AnimationFrame {
+    fn drop(&mut self) {
+        if self.shared.borrow().0.is_none() {
+            WINDOW.with(|window| window.cancel_animation_frame(self.id).unwrap_throw());
+        }
+    }
+}
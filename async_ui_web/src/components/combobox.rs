@@ -0,0 +1,472 @@
+use std::{
+    cell::{Cell, RefCell},
+    future::Future,
+    ops::{Deref, Range},
+    time::Duration,
+};
+
+use async_ui_web_core::{window::DOCUMENT, ContainerNodeFuture};
+use async_ui_web_html::{
+    events::{EmitElementEvent, EmitHtmlElementEvent},
+    nodes::{Input, Li, Ul},
+};
+use futures_lite::Stream;
+use gloo_timers::future::TimeoutFuture;
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::Node;
+
+use crate::{
+    accessibility::ShortcutAria,
+    join,
+    positioning::{follow_anchor, overlay_root, Align, Placement, PositionOptions, Side},
+    race,
+    style::ShortcutStyle,
+    NoChild, ReactiveCell,
+};
+
+/// How long to wait after the last keystroke before asking for suggestions.
+const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/**
+A text input that suggests options as the user types.
+
+Suggestions come from an async function given to [render][Self::render],
+called with the text in the input. It is called once the user stops typing
+(see [set_debounce][Self::set_debounce]), and if the user types again or picks
+a suggestion before it completes, its future is dropped, so stale suggestions
+never show.
+
+The suggestions are shown in a list below the input. The user picks one with
+the mouse, or with <kbd>↑</kbd>/<kbd>↓</kbd> and <kbd>Enter</kbd>;
+<kbd>Escape</kbd> closes the list. The part of each suggestion that matches
+the text is wrapped in `<mark>`.
+
+Like [Dropdown][super::Dropdown], options are identified by values of type `O`,
+and [value][Self::value] gets the one the user picked.
+
+This follows the [ARIA combobox pattern](https://www.w3.org/WAI/ARIA/apg/patterns/combobox/),
+with the input keeping focus while the user moves through the suggestions.
+
+```
+# use async_ui_web::{components::Combobox, join};
+# struct City { id: u32, name: String }
+# async fn search_cities(query: &str) -> Vec<City> { vec![] }
+# let _ = async {
+let combobox = Combobox::<u32>::new();
+join((
+    combobox.render(|query| async move {
+        search_cities(&query)
+            .await
+            .into_iter()
+            .map(|city| (city.id, city.name))
+            .collect()
+    }),
+    async {
+        loop {
+            combobox.until_change().await;
+            let city_id: Option<u32> = combobox.value();
+        }
+    },
+))
+.await;
+# };
+```
+
+This type [Deref]s to [Input] and [HtmlInputElement][web_sys::HtmlInputElement],
+so you can use all the HTML methods (such as [set_placeholder][web_sys::HtmlInputElement::set_placeholder]) on it.
+*/
+pub struct Combobox<O: Clone> {
+    input: Input,
+    listbox: Ul,
+    inner: RefCell<Inner<O>>,
+    selected: ReactiveCell<Option<O>>,
+    debounce: Cell<Duration>,
+}
+
+struct Inner<O> {
+    /// The suggestions being shown.
+    options: Vec<(O, String, Li)>,
+    /// The suggestion highlighted with the arrow keys.
+    active: Option<usize>,
+}
+
+impl<O: Clone> Default for Combobox<O> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<O: Clone> Deref for Combobox<O> {
+    type Target = Input;
+    fn deref(&self) -> &Self::Target {
+        &self.input
+    }
+}
+
+impl<O: Clone> Combobox<O> {
+    /// Create a new, empty Combobox.
+    pub fn new() -> Self {
+        let input = Input::new();
+        let listbox = Ul::new();
+        input.set_attribute("role", "combobox").unwrap_throw();
+        input
+            .set_attribute("aria-autocomplete", "list")
+            .unwrap_throw();
+        input.set_attribute("aria-expanded", "false").unwrap_throw();
+        input.set_attribute("autocomplete", "off").unwrap_throw();
+        input
+            .set_attribute("aria-controls", &listbox.aria_id())
+            .unwrap_throw();
+        listbox.set_attribute("role", "listbox").unwrap_throw();
+        listbox.add_aria_labelledby(&input);
+        listbox.set_hidden(true);
+        Self {
+            input,
+            listbox,
+            inner: RefCell::new(Inner {
+                options: Vec::new(),
+                active: None,
+            }),
+            selected: ReactiveCell::new(None),
+            debounce: Cell::new(DEFAULT_DEBOUNCE),
+        }
+    }
+    /// The `<ul>` that the suggestions are in. Use this to style the list.
+    ///
+    /// Each suggestion is an `<li role="option">`. The one highlighted with
+    /// the arrow keys has `aria-selected="true"`.
+    pub fn listbox(&self) -> &Ul {
+        &self.listbox
+    }
+    /// How long to wait after the user stops typing before asking for
+    /// suggestions. The default is 200ms.
+    pub fn set_debounce(&self, debounce: Duration) {
+        self.debounce.set(debounce);
+    }
+    /// Get the option the user picked.
+    ///
+    /// Returns `None` if nothing is picked, or if the user has edited the
+    /// text since picking.
+    pub fn value(&self) -> Option<O> {
+        self.selected.borrow().clone()
+    }
+    /// Set the picked option, and the text shown in the input.
+    ///
+    /// With `None`, deselect the current selection and clear the text.
+    pub fn set_value(&self, value: Option<(O, &str)>) {
+        let (value, text) = match value {
+            Some((value, text)) => (Some(value), text),
+            None => (None, ""),
+        };
+        self.input.set_value(text);
+        *self.selected.borrow_mut() = value;
+    }
+    /// Get a [Stream] that fires every time the picked option changes.
+    ///
+    /// This fires when the user picks a suggestion, and when the user edits
+    /// the text of a picked option (so that nothing is picked).
+    pub fn until_change(&self) -> impl Future<Output = ()> + Stream<Item = ()> + '_ {
+        self.selected.until_change()
+    }
+    /// Render the combobox, getting suggestions from `suggest`.
+    ///
+    /// `suggest` is called with the text in the input, and returns the
+    /// suggestions to show: each an option and the text to display for it.
+    /// It is not called when the input is empty.
+    ///
+    /// This async method never completes.
+    pub async fn render<F: Future<Output = Vec<(O, String)>>>(
+        &self,
+        suggest: impl Fn(String) -> F,
+    ) {
+        let options = PositionOptions {
+            placement: Placement {
+                side: Side::Bottom,
+                align: Align::Start,
+            },
+            offset: 2.0,
+            ..Default::default()
+        };
+        join((
+            self.input.render(),
+            ContainerNodeFuture::new_root(self.listbox.render(NoChild), overlay_root().into()),
+            follow_anchor(&self.input, &self.listbox, &options),
+            self.fetch_suggestions(suggest),
+            self.handle_keys(),
+            self.handle_pointer(),
+        ))
+        .await;
+    }
+    /// Ask for suggestions whenever the text changes.
+    async fn fetch_suggestions<F: Future<Output = Vec<(O, String)>>>(
+        &self,
+        suggest: impl Fn(String) -> F,
+    ) {
+        let mut inputs = self.input.until_input();
+        let mut changed = false;
+        loop {
+            if !changed {
+                (&mut inputs).await;
+            }
+            if self.selected.borrow().is_some() {
+                *self.selected.borrow_mut() = None;
+            }
+            let query = self.input.value();
+            if query.is_empty() {
+                self.show_suggestions(&query, Vec::new());
+                changed = false;
+                continue;
+            }
+            // typing again drops this future, cancelling the request,
+            // and so does picking a suggestion (or setting the value) meanwhile
+            let mut picks = self.selected.until_change();
+            changed = race((
+                async {
+                    TimeoutFuture::new(self.debounce.get().as_millis() as u32).await;
+                    self.listbox
+                        .set_attribute("aria-busy", "true")
+                        .unwrap_throw();
+                    let _busy = scopeguard::guard((), |_| {
+                        self.listbox.remove_attribute("aria-busy").unwrap_throw();
+                    });
+                    let suggestions = suggest(query.clone()).await;
+                    self.show_suggestions(&query, suggestions);
+                    false
+                },
+                async {
+                    (&mut inputs).await;
+                    true
+                },
+                async {
+                    (&mut picks).await;
+                    false
+                },
+            ))
+            .await;
+        }
+    }
+    async fn handle_keys(&self) {
+        let mut keydowns = self.input.until_keydown();
+        loop {
+            let ev = (&mut keydowns).await;
+            if ev.ctrl_key() || ev.alt_key() || ev.meta_key() || ev.is_composing() {
+                continue;
+            }
+            let open = !self.listbox.hidden();
+            match ev.key().as_str() {
+                key @ ("ArrowDown" | "ArrowUp") => {
+                    ev.prevent_default();
+                    let len = self.inner.borrow().options.len();
+                    if !open {
+                        self.set_open(len > 0);
+                        continue;
+                    }
+                    let active = self.inner.borrow().active;
+                    self.set_active(next_active(active, len, key == "ArrowDown"));
+                }
+                "Enter" if open => {
+                    let active = self.inner.borrow().active;
+                    if let Some(index) = active {
+                        ev.prevent_default();
+                        self.pick(index);
+                    }
+                }
+                "Escape" if open => {
+                    ev.prevent_default();
+                    self.set_open(false);
+                }
+                _ => {}
+            }
+        }
+    }
+    async fn handle_pointer(&self) {
+        let mut mousedowns = self.listbox.until_mousedown();
+        let mut clicks = self.listbox.until_click();
+        let mut focusouts = self.input.until_focusout();
+        race((
+            async {
+                loop {
+                    // keep focus in the input
+                    (&mut mousedowns).await.prevent_default();
+                }
+            },
+            async {
+                loop {
+                    let ev = (&mut clicks).await;
+                    let target = ev.target().and_then(|t| t.dyn_into::<Node>().ok());
+                    let clicked = self
+                        .inner
+                        .borrow()
+                        .options
+                        .iter()
+                        .position(|(_, _, li)| li.contains(target.as_ref()));
+                    if let Some(index) = clicked {
+                        self.pick(index);
+                    }
+                }
+            },
+            async {
+                loop {
+                    (&mut focusouts).await;
+                    self.set_open(false);
+                }
+            },
+        ))
+        .await;
+    }
+    /// Replace the suggestions in the list.
+    fn show_suggestions(&self, query: &str, suggestions: Vec<(O, String)>) {
+        let mut inner = self.inner.borrow_mut();
+        for (_, _, li) in inner.options.drain(..) {
+            li.remove();
+        }
+        inner.active = None;
+        for (value, label) in suggestions {
+            let li = Li::new();
+            li.set_attribute("role", "option").unwrap_throw();
+            li.set_attribute("aria-selected", "false").unwrap_throw();
+            li.aria_id();
+            fill_highlighted(&li, &label, query);
+            self.listbox.append_child(&li).unwrap_throw();
+            inner.options.push((value, label, li));
+        }
+        let any = !inner.options.is_empty();
+        drop(inner);
+        self.input
+            .remove_attribute("aria-activedescendant")
+            .unwrap_throw();
+        self.set_open(any && self.is_focused());
+    }
+    fn set_open(&self, open: bool) {
+        if open {
+            let width = self.input.get_bounding_client_rect().width();
+            self.listbox
+                .set_style("min-width", format_args!("{width}px"));
+        } else {
+            self.set_active(None);
+        }
+        self.listbox.set_hidden(!open);
+        self.input
+            .set_attribute("aria-expanded", if open { "true" } else { "false" })
+            .unwrap_throw();
+    }
+    fn set_active(&self, active: Option<usize>) {
+        let mut inner = self.inner.borrow_mut();
+        if let Some((_, _, li)) = inner.active.and_then(|index| inner.options.get(index)) {
+            li.set_attribute("aria-selected", "false").unwrap_throw();
+        }
+        inner.active = active;
+        match active.and_then(|index| inner.options.get(index)) {
+            Some((_, _, li)) => {
+                li.set_attribute("aria-selected", "true").unwrap_throw();
+                self.input
+                    .set_attribute("aria-activedescendant", &li.id())
+                    .unwrap_throw();
+                li.scroll_into_view_with_bool(false);
+            }
+            None => {
+                self.input
+                    .remove_attribute("aria-activedescendant")
+                    .unwrap_throw();
+            }
+        }
+    }
+    /// Pick the suggestion at the given index.
+    fn pick(&self, index: usize) {
+        let Some((value, label)) = self
+            .inner
+            .borrow()
+            .options
+            .get(index)
+            .map(|(value, label, _)| (value.clone(), label.clone()))
+        else {
+            return;
+        };
+        self.set_open(false);
+        self.set_value(Some((value, &label)));
+    }
+    fn is_focused(&self) -> bool {
+        DOCUMENT.with(|doc| doc.active_element()).as_ref() == Some(self.input.as_ref())
+    }
+}
+
+/// Set the content of the element to the label, with the part matching the
+/// query wrapped in `<mark>`.
+fn fill_highlighted(element: &Li, label: &str, query: &str) {
+    let Some(range) = find_match(label, query) else {
+        element.set_text_content(Some(label));
+        return;
+    };
+    let mark = DOCUMENT.with(|doc| doc.create_element("mark").unwrap_throw());
+    mark.set_text_content(Some(&label[range.clone()]));
+    element
+        .append_with_str_1(&label[..range.start])
+        .unwrap_throw();
+    element.append_child(&mark).unwrap_throw();
+    element
+        .append_with_str_1(&label[range.end..])
+        .unwrap_throw();
+}
+
+/// Find the first case-insensitive occurrence of the query in the label.
+fn find_match(label: &str, query: &str) -> Option<Range<usize>> {
+    if query.is_empty() {
+        return None;
+    }
+    let lower = |s: &str| s.chars().flat_map(char::to_lowercase).collect::<Vec<_>>();
+    let query = lower(query);
+    label.char_indices().find_map(|(start, _)| {
+        let mut end = start;
+        let mut want = query.iter().peekable();
+        for ch in label[start..].chars() {
+            if want.peek().is_none() {
+                break;
+            }
+            for lower_ch in ch.to_lowercase() {
+                if want.next() != Some(&lower_ch) {
+                    return None;
+                }
+            }
+            end += ch.len_utf8();
+        }
+        want.peek().is_none().then_some(start..end)
+    })
+}
+
+/// The suggestion to highlight after pressing up or down.
+fn next_active(active: Option<usize>, len: usize, down: bool) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    Some(match (active, down) {
+        (None, true) => 0,
+        (None, false) => len - 1,
+        (Some(index), true) => (index + 1) % len,
+        (Some(index), false) => (index + len - 1) % len,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matching() {
+        assert_eq!(find_match("New York", "york"), Some(4..8));
+        assert_eq!(find_match("New York", "NEW"), Some(0..3));
+        assert_eq!(find_match("New York", "boston"), None);
+        assert_eq!(find_match("New York", ""), None);
+        assert_eq!(find_match("Zürich", "RI"), Some(3..5));
+        assert_eq!(find_match("Yo", "York"), None);
+    }
+
+    #[test]
+    fn arrow_keys() {
+        assert_eq!(next_active(None, 3, true), Some(0));
+        assert_eq!(next_active(None, 3, false), Some(2));
+        assert_eq!(next_active(Some(2), 3, true), Some(0));
+        assert_eq!(next_active(Some(0), 3, false), Some(2));
+        assert_eq!(next_active(Some(1), 3, true), Some(2));
+        assert_eq!(next_active(None, 0, true), None);
+    }
+}
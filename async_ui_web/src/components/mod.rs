@@ -2,8 +2,26 @@
 //!
 //! Components in this module are provided for convenience.
 
+mod canvas;
+mod combobox;
 mod dropdown;
 mod dynamic_slot;
+mod modal;
+mod popover;
+mod raw_html;
+mod reorder;
+mod shadow_root;
+mod tabs;
+mod toast;
 
+pub use canvas::Canvas2d;
+pub use combobox::Combobox;
 pub use dropdown::Dropdown;
 pub use dynamic_slot::DynamicSlot;
+pub use modal::{confirm, Modal};
+pub use popover::{Popover, Tooltip};
+pub use raw_html::{RawHtml, Sanitizer};
+pub use reorder::{ListId, Reorder, ReorderGroup, ReorderMove};
+pub use shadow_root::ShadowRoot;
+pub use tabs::{PanelMode, Tabs};
+pub use toast::{toast, ToastHandle, ToastHost, ToastOptions};
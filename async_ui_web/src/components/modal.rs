@@ -0,0 +1,204 @@
+use std::{cell::Cell, future::Future, ops::Deref};
+
+use async_ui_web_core::{window::DOCUMENT, ContainerNodeFuture};
+use async_ui_web_html::{
+    events::{EmitElementEvent, EmitEvent},
+    nodes::{Button, Dialog, Div, Paragraph},
+};
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::HtmlElement;
+
+use crate::{focus::FocusTrap, race, shortcut_traits::ShortcutRenderStr};
+
+/**
+A modal dialog that resolves with the output of its content.
+
+The dialog is shown with the native
+[showModal](https://developer.mozilla.org/en-US/docs/Web/API/HTMLDialogElement/showModal),
+so it is on top of everything else and the rest of the page can't be
+interacted with. Keyboard focus is kept inside the dialog, and goes back to
+where it was when the dialog closes.
+
+[show_modal][Modal::show_modal] can be awaited from anywhere;
+the `<dialog>` is added to the `<body>` of the document while it is shown.
+
+```
+# use async_ui_web::{components::Modal, html::{Button, Input}, prelude_traits::*, race};
+# let _ = async {
+let modal = Modal::new();
+let name = Input::new();
+let ok = Button::new();
+let entered: Option<String> = modal
+    .show_modal(async {
+        race((
+            name.render(),
+            ok.render("OK".render()),
+            async {
+                ok.until_click().await;
+            },
+        ))
+        .await;
+        name.value()
+    })
+    .await;
+// `entered` is None if the user pressed Escape or clicked outside
+# };
+```
+
+This type [Deref]s to [Dialog], so you can use all the HTML methods on it.
+*/
+pub struct Modal {
+    dialog: Dialog,
+    close_on_escape: Cell<bool>,
+    close_on_backdrop: Cell<bool>,
+}
+
+impl Default for Modal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for Modal {
+    type Target = Dialog;
+    fn deref(&self) -> &Self::Target {
+        &self.dialog
+    }
+}
+
+impl Modal {
+    /// Create a new modal dialog.
+    pub fn new() -> Self {
+        Self {
+            dialog: Dialog::new(),
+            close_on_escape: Cell::new(true),
+            close_on_backdrop: Cell::new(true),
+        }
+    }
+    /// Whether pressing Escape closes the dialog. The default is true.
+    pub fn set_close_on_escape(&self, close: bool) {
+        self.close_on_escape.set(close);
+    }
+    /// Whether clicking outside the dialog (on the backdrop) closes it.
+    /// The default is true.
+    pub fn set_close_on_backdrop(&self, close: bool) {
+        self.close_on_backdrop.set(close);
+    }
+    /// Show the dialog with the given content, and wait for it to close.
+    ///
+    /// Returns `Some` with the output of `content` once it completes,
+    /// or `None` if the user closed the dialog some other way.
+    /// The dialog is closed (and `content` dropped) when this returns,
+    /// or when the returned future is dropped.
+    pub async fn show_modal<F: Future>(&self, content: F) -> Option<F::Output> {
+        let body = DOCUMENT.with(|doc| doc.body().unwrap_throw());
+        let previous = DOCUMENT
+            .with(|doc| doc.active_element())
+            .and_then(|element| element.dyn_into::<HtmlElement>().ok());
+        body.append_child(&self.dialog).unwrap_throw();
+        let _guard = scopeguard::guard((), |_| {
+            self.dialog.close();
+            self.dialog.remove();
+            // the focus trap gives focus back before this, while the rest of
+            // the page is still inert, so do it again
+            if let Some(previous) = previous {
+                let _ = previous.focus();
+            }
+        });
+        let trap = FocusTrap::new();
+        race((
+            // render into the dialog wherever we are being awaited
+            ContainerNodeFuture::new_root(
+                async {
+                    self.dialog.show_modal().unwrap_throw();
+                    Some(trap.render(content).await)
+                },
+                self.dialog.clone().into(),
+            ),
+            self.until_dismissed(),
+        ))
+        .await
+    }
+    /// Wait for the user to close the dialog with Escape or the backdrop.
+    async fn until_dismissed<T>(&self) -> Option<T> {
+        let mut cancels = self.dialog.until_event::<web_sys::Event>("cancel".into());
+        let mut closes = self.dialog.until_event::<web_sys::Event>("close".into());
+        let mut clicks = self.dialog.until_click();
+        loop {
+            let dismissed = race((
+                async {
+                    // fired when Escape is pressed
+                    let ev = (&mut cancels).await;
+                    // we close the dialog ourselves, so that the content is dropped
+                    ev.prevent_default();
+                    self.close_on_escape.get()
+                },
+                async {
+                    // the browser may close the dialog even if `cancel` was prevented
+                    (&mut closes).await;
+                    true
+                },
+                async {
+                    let ev = (&mut clicks).await;
+                    // clicks on the backdrop target the dialog itself,
+                    // but land outside its box
+                    let rect = self.dialog.get_bounding_client_rect();
+                    let (x, y) = (ev.client_x() as f64, ev.client_y() as f64);
+                    let outside =
+                        x < rect.left() || x > rect.right() || y < rect.top() || y > rect.bottom();
+                    let on_dialog = ev.target().as_ref() == Some(self.dialog.as_ref());
+                    on_dialog && outside && self.close_on_backdrop.get()
+                },
+            ))
+            .await;
+            if dismissed {
+                return None;
+            }
+        }
+    }
+}
+
+/// Show a modal dialog with the given message, and "Cancel" and "OK" buttons.
+///
+/// Returns true if the user pressed OK.
+///
+/// ```
+/// # use async_ui_web::components::confirm;
+/// # let _ = async {
+/// if confirm("Delete this file?").await {
+///     // delete the file
+/// }
+/// # };
+/// ```
+pub async fn confirm(message: &str) -> bool {
+    let modal = Modal::new();
+    let cancel = Button::new();
+    let ok = Button::new();
+    modal
+        .show_modal(race((
+            async {
+                Paragraph::new().render(message.render()).await;
+                false
+            },
+            Div::new().render(race((
+                async {
+                    cancel.render("Cancel".render()).await;
+                    false
+                },
+                async {
+                    ok.render("OK".render()).await;
+                    false
+                },
+                async {
+                    cancel.until_click().await;
+                    false
+                },
+                async {
+                    ok.until_click().await;
+                    true
+                },
+            ))),
+        )))
+        .await
+        .unwrap_or(false)
+}
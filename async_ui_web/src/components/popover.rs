@@ -0,0 +1,339 @@
+use std::{
+    cell::{Cell, RefCell},
+    future::{pending, Future},
+    time::Duration,
+};
+
+use async_ui_web_core::{window::DOCUMENT, ContainerNodeFuture};
+use async_ui_web_html::{
+    events::{EmitElementEvent, EmitEvent},
+    nodes::Div,
+};
+use futures_lite::StreamExt;
+use gloo_timers::future::TimeoutFuture;
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::{HtmlElement, KeyboardEvent, Node};
+
+use crate::{
+    accessibility::ShortcutAria,
+    join,
+    positioning::{follow_anchor, overlay_root, Align, Placement, PositionOptions, Side},
+    race, ReactiveCell,
+};
+
+/// How long the pointer has to rest on an anchor before its tooltip shows.
+const DEFAULT_TOOLTIP_DELAY: Duration = Duration::from_millis(500);
+
+/**
+A tooltip that shows when its anchor element is hovered or focused.
+
+The tooltip content is rendered into a `<div role="tooltip">` in the
+[overlay root][crate::positioning::overlay_root], and positioned next to the
+anchor with [follow_anchor]. The anchor gets an `aria-describedby` pointing at
+the tooltip.
+
+The tooltip hides when the pointer leaves the anchor, the anchor loses focus,
+or <kbd>Escape</kbd> is pressed.
+
+```
+# use async_ui_web::{components::Tooltip, html::Button, join, prelude_traits::*};
+# let _ = async {
+let button = Button::new();
+let tooltip = Tooltip::new();
+join((
+    button.render("Save".render()),
+    tooltip.render(&button, "Save your changes (Ctrl+S)".render()),
+))
+.await;
+# };
+```
+*/
+pub struct Tooltip {
+    floating: Div,
+    options: RefCell<PositionOptions>,
+    delay: Cell<Duration>,
+}
+
+impl Default for Tooltip {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tooltip {
+    /// Create a tooltip. It goes above the anchor by default.
+    pub fn new() -> Self {
+        let floating = Div::new();
+        floating.set_attribute("role", "tooltip").unwrap_throw();
+        floating.set_hidden(true);
+        Self {
+            floating,
+            options: RefCell::new(PositionOptions {
+                placement: Placement::new(Side::Top),
+                offset: 6.0,
+                ..Default::default()
+            }),
+            delay: Cell::new(DEFAULT_TOOLTIP_DELAY),
+        }
+    }
+    /// The `<div>` that the content is in. Use this to style the tooltip.
+    pub fn floating(&self) -> &Div {
+        &self.floating
+    }
+    /// Set where the tooltip goes. This takes effect the next time it shows.
+    pub fn set_options(&self, options: PositionOptions) {
+        *self.options.borrow_mut() = options;
+    }
+    /// How long the pointer has to rest on the anchor before the tooltip
+    /// shows. The default is 500ms. Focusing the anchor shows it immediately.
+    pub fn set_delay(&self, delay: Duration) {
+        self.delay.set(delay);
+    }
+    /// Attach the tooltip to the anchor, and render the content in it.
+    ///
+    /// The anchor must be rendered separately. The content stays alive
+    /// (hidden) while the tooltip isn't showing.
+    ///
+    /// This async method never completes.
+    pub async fn render<F: Future<Output = ()>>(
+        &self,
+        anchor: &impl AsRef<HtmlElement>,
+        content: F,
+    ) {
+        let anchor = anchor.as_ref();
+        anchor.add_aria_describedby(&self.floating);
+        let _guard = scopeguard::guard((), |_| {
+            anchor.remove_aria_describedby(&self.floating);
+        });
+        join((
+            ContainerNodeFuture::new_root(self.floating.render(content), overlay_root().into()),
+            self.show_and_hide(anchor),
+        ))
+        .await;
+    }
+    async fn show_and_hide(&self, anchor: &HtmlElement) {
+        loop {
+            self.until_show(anchor).await;
+            self.floating.set_hidden(false);
+            let options = self.options.borrow().clone();
+            race((
+                follow_anchor(anchor, &self.floating, &options),
+                Self::until_hide(anchor),
+            ))
+            .await;
+            self.floating.set_hidden(true);
+        }
+    }
+    /// Wait for the pointer to rest on the anchor, or for the anchor to get focus.
+    async fn until_show(&self, anchor: &HtmlElement) {
+        // created fresh each time, so that events from earlier don't count
+        let mut enters = anchor.until_event::<web_sys::Event>("pointerenter".into());
+        let mut leaves = anchor.until_event::<web_sys::Event>("pointerleave".into());
+        let mut focusins = anchor.until_focusin();
+        race((
+            async {
+                loop {
+                    (&mut enters).await;
+                    let rested = race((
+                        async {
+                            TimeoutFuture::new(self.delay.get().as_millis() as u32).await;
+                            true
+                        },
+                        async {
+                            (&mut leaves).await;
+                            false
+                        },
+                    ))
+                    .await;
+                    if rested {
+                        return;
+                    }
+                }
+            },
+            async {
+                (&mut focusins).await;
+            },
+        ))
+        .await
+    }
+    /// Wait for the pointer to leave, the anchor to lose focus, or Escape.
+    async fn until_hide(anchor: &HtmlElement) {
+        let mut leaves = anchor.until_event::<web_sys::Event>("pointerleave".into());
+        let mut focusouts = anchor.until_focusout();
+        let mut keydowns = DOCUMENT.with(|doc| doc.until_event::<KeyboardEvent>("keydown".into()));
+        race((
+            async {
+                (&mut leaves).await;
+            },
+            async {
+                (&mut focusouts).await;
+            },
+            async { while (&mut keydowns).await.key() != "Escape" {} },
+        ))
+        .await
+    }
+}
+
+/**
+A popover that opens when its anchor element is clicked.
+
+While open, the content is rendered into a `<div role="dialog">` in the
+[overlay root][crate::positioning::overlay_root], and positioned next to the
+anchor with [follow_anchor]. The anchor gets `aria-expanded` and
+`aria-controls` attributes.
+
+Clicking the anchor again, clicking outside the popover, or pressing
+<kbd>Escape</kbd> closes the popover. The open state is in a [ReactiveCell];
+set it to open or close the popover yourself.
+
+```
+# use async_ui_web::{components::Popover, html::Button, join, prelude_traits::*};
+# async fn filter_options() {}
+# let _ = async {
+let button = Button::new();
+let popover = Popover::new();
+join((
+    button.render("Filters".render()),
+    popover.render(&button, filter_options),
+))
+.await;
+# };
+```
+*/
+pub struct Popover {
+    floating: Div,
+    options: RefCell<PositionOptions>,
+    open: ReactiveCell<bool>,
+}
+
+impl Default for Popover {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Popover {
+    /// Create a closed popover. It goes below the anchor by default,
+    /// lined up with its start.
+    pub fn new() -> Self {
+        let floating = Div::new();
+        floating.set_attribute("role", "dialog").unwrap_throw();
+        Self {
+            floating,
+            options: RefCell::new(PositionOptions {
+                placement: Placement {
+                    side: Side::Bottom,
+                    align: Align::Start,
+                },
+                offset: 4.0,
+                ..Default::default()
+            }),
+            open: ReactiveCell::new(false),
+        }
+    }
+    /// The `<div>` that the content is in. Use this to style the popover.
+    pub fn floating(&self) -> &Div {
+        &self.floating
+    }
+    /// Set where the popover goes. This takes effect the next time it opens.
+    pub fn set_options(&self, options: PositionOptions) {
+        *self.options.borrow_mut() = options;
+    }
+    /// Whether the popover is open.
+    pub fn open(&self) -> &ReactiveCell<bool> {
+        &self.open
+    }
+    /// Open or close the popover.
+    pub fn set_open(&self, open: bool) {
+        *self.open.borrow_mut() = open;
+    }
+    /// Attach the popover to the anchor.
+    ///
+    /// The anchor must be rendered separately. `content` is called to create
+    /// the content every time the popover opens, and the content is dropped
+    /// when it closes.
+    ///
+    /// This async method never completes.
+    pub async fn render<F: Future<Output = ()>>(
+        &self,
+        anchor: &impl AsRef<HtmlElement>,
+        content: impl Fn() -> F,
+    ) {
+        let anchor = anchor.as_ref();
+        anchor
+            .set_attribute("aria-haspopup", "dialog")
+            .unwrap_throw();
+        anchor
+            .set_attribute("aria-controls", &self.floating.aria_id())
+            .unwrap_throw();
+        let mut open_changes = self.open.until_change();
+        let mut clicks = anchor.until_click();
+        join((
+            async {
+                loop {
+                    (&mut clicks).await;
+                    let open = *self.open.borrow();
+                    self.set_open(!open);
+                }
+            },
+            async {
+                loop {
+                    let open = *self.open.borrow();
+                    anchor
+                        .set_attribute("aria-expanded", if open { "true" } else { "false" })
+                        .unwrap_throw();
+                    if !open {
+                        open_changes.next().await;
+                        continue;
+                    }
+                    let options = self.options.borrow().clone();
+                    race((
+                        ContainerNodeFuture::new_root(
+                            self.floating.render(async {
+                                content().await;
+                                pending::<()>().await;
+                            }),
+                            overlay_root().into(),
+                        ),
+                        follow_anchor(anchor, &self.floating, &options),
+                        async {
+                            while *self.open.borrow() {
+                                open_changes.next().await;
+                            }
+                        },
+                        self.until_dismissed(anchor),
+                    ))
+                    .await;
+                    self.set_open(false);
+                }
+            },
+        ))
+        .await;
+    }
+    /// Wait for a click outside the popover and the anchor, or Escape.
+    async fn until_dismissed(&self, anchor: &HtmlElement) {
+        let mut pointerdowns =
+            DOCUMENT.with(|doc| doc.until_event::<web_sys::Event>("pointerdown".into()));
+        pointerdowns.set_capture(true);
+        let mut keydowns = DOCUMENT.with(|doc| doc.until_event::<KeyboardEvent>("keydown".into()));
+        race((
+            async {
+                loop {
+                    let ev = (&mut pointerdowns).await;
+                    let target = ev.target().and_then(|t| t.dyn_into::<Node>().ok());
+                    let inside =
+                        anchor.contains(target.as_ref()) || self.floating.contains(target.as_ref());
+                    if !inside {
+                        return;
+                    }
+                }
+            },
+            async {
+                while (&mut keydowns).await.key() != "Escape" {}
+                // focus may have been inside the popover, which is going away
+                let _ = anchor.focus();
+            },
+        ))
+        .await
+    }
+}
@@ -0,0 +1,320 @@
+use std::{collections::HashSet, future::Future};
+
+use async_ui_web_core::{window::DOCUMENT, FragmentNodeFuture};
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::{Element, HtmlTemplateElement, Node};
+
+/**
+A fragment of HTML parsed from a string.
+
+By default, the HTML is sanitized with the default [Sanitizer]:
+only allowlisted tags, attributes, and URL schemes are kept.
+Use [RawHtml::trusted] to skip sanitizing HTML that you control.
+
+```
+# use async_ui_web::components::RawHtml;
+# let _ = async {
+# let user_comment = String::new();
+RawHtml::new(&user_comment).render().await;
+# };
+```
+
+The nodes are inserted into the page together, as one unit, at the position
+where `render` is called. They stay there until the returned Future is dropped.
+There is no wrapper element, so the HTML can go anywhere its nodes are valid,
+such as rows inside a `<table>` or items inside a `<ul>`.
+*/
+pub struct RawHtml {
+    nodes: Vec<Node>,
+}
+
+impl RawHtml {
+    /// Parse the HTML string, and sanitize it with the default [Sanitizer].
+    pub fn new(html: &str) -> Self {
+        Self::new_sanitized(html, &Sanitizer::default())
+    }
+    /// Parse the HTML string, and sanitize it with the given [Sanitizer].
+    pub fn new_sanitized(html: &str, sanitizer: &Sanitizer) -> Self {
+        let template = parse(html);
+        sanitizer.sanitize_children(&template.content());
+        Self::from_template(template)
+    }
+    /// Parse the HTML string **without sanitizing it**.
+    ///
+    /// Only use this for HTML that can't contain user input.
+    /// Scripts in the HTML won't run, but event handler attributes
+    /// (like `onerror`) will.
+    pub fn trusted(html: &str) -> Self {
+        Self::from_template(parse(html))
+    }
+    fn from_template(template: HtmlTemplateElement) -> Self {
+        let children = template.content().child_nodes();
+        let nodes = (0..children.length())
+            .filter_map(|idx| children.get(idx))
+            .collect();
+        Self { nodes }
+    }
+    /// Put the nodes on the screen.
+    ///
+    /// The returned Future never completes.
+    /// When it is dropped, the nodes are removed.
+    pub fn render(&self) -> impl Future<Output = ()> + '_ {
+        FragmentNodeFuture::new(self.nodes.clone())
+    }
+}
+
+/// Parse the HTML inside an inert `<template>`, so that nothing loads or runs.
+fn parse(html: &str) -> HtmlTemplateElement {
+    let template: HtmlTemplateElement = DOCUMENT.with(|doc| {
+        doc.create_element("template")
+            .unwrap_throw()
+            .unchecked_into()
+    });
+    template.set_inner_html(html);
+    template
+}
+
+/// Elements that are removed together with their content.
+/// Other disallowed elements are replaced by their (sanitized) children.
+const REMOVE_WITH_CONTENT: &[&str] = &[
+    "script", "style", "template", "iframe", "frame", "frameset", "object", "embed", "noscript",
+    "noembed", "textarea", "select", "title", "head", "math", "svg",
+];
+
+/// Attributes whose value is a URL, and should be checked with [Sanitizer::is_url_allowed].
+const URL_ATTRIBUTES: &[&str] = &[
+    "href",
+    "src",
+    "cite",
+    "action",
+    "formaction",
+    "poster",
+    "background",
+    "xlink:href",
+];
+
+/// An allowlist-based HTML sanitizer, for use with [RawHtml::new_sanitized].
+///
+/// * Elements not in the tag allowlist are replaced by their children
+///   (or removed entirely, for elements like `<script>` and `<style>`).
+/// * Attributes not in the attribute allowlist are removed.
+///   Event handler attributes (`on*`) and `style` are never allowed.
+/// * URL attributes (like `href` and `src`) are removed if their scheme is
+///   not in the scheme allowlist. Relative URLs are allowed.
+/// * Comments are removed.
+///
+/// The sanitized nodes are inserted directly, without being serialized
+/// and parsed again, so mutation-based tricks don't apply.
+///
+/// ```
+/// # use async_ui_web::components::{RawHtml, Sanitizer};
+/// # let _ = async {
+/// let sanitizer = Sanitizer::default()
+///     .allow_tags(["details", "summary"])
+///     .allow_attributes(["open"])
+///     .allow_url_schemes(["ftp"]);
+/// # let html = "";
+/// let fragment = RawHtml::new_sanitized(html, &sanitizer);
+/// # };
+/// ```
+#[derive(Clone, Debug)]
+pub struct Sanitizer {
+    tags: HashSet<&'static str>,
+    attributes: HashSet<&'static str>,
+    url_schemes: HashSet<&'static str>,
+}
+
+impl Default for Sanitizer {
+    /// Allow common formatting tags, links, images, lists, and tables,
+    /// with `http`, `https`, `mailto`, and `tel` URLs.
+    fn default() -> Self {
+        Self::empty()
+            .allow_tags([
+                "a",
+                "abbr",
+                "b",
+                "blockquote",
+                "br",
+                "code",
+                "del",
+                "div",
+                "em",
+                "figcaption",
+                "figure",
+                "h1",
+                "h2",
+                "h3",
+                "h4",
+                "h5",
+                "h6",
+                "hr",
+                "i",
+                "img",
+                "ins",
+                "li",
+                "mark",
+                "ol",
+                "p",
+                "pre",
+                "s",
+                "small",
+                "span",
+                "strong",
+                "sub",
+                "sup",
+                "table",
+                "tbody",
+                "td",
+                "tfoot",
+                "th",
+                "thead",
+                "tr",
+                "u",
+                "ul",
+            ])
+            .allow_attributes([
+                "alt", "class", "colspan", "dir", "height", "href", "lang", "rowspan", "src",
+                "title", "width",
+            ])
+            .allow_url_schemes(["http", "https", "mailto", "tel"])
+    }
+}
+
+impl Sanitizer {
+    /// Create a sanitizer that allows nothing but text.
+    pub fn empty() -> Self {
+        Self {
+            tags: HashSet::new(),
+            attributes: HashSet::new(),
+            url_schemes: HashSet::new(),
+        }
+    }
+    /// Add tags (lowercase) to the allowlist.
+    pub fn allow_tags(mut self, tags: impl IntoIterator<Item = &'static str>) -> Self {
+        self.tags.extend(tags);
+        self
+    }
+    /// Add attributes (lowercase) to the allowlist.
+    ///
+    /// Event handler attributes (`on*`) and `style` are rejected even if added here.
+    pub fn allow_attributes(mut self, attributes: impl IntoIterator<Item = &'static str>) -> Self {
+        self.attributes.extend(attributes);
+        self
+    }
+    /// Add URL schemes (lowercase, without the colon) to the allowlist.
+    pub fn allow_url_schemes(mut self, schemes: impl IntoIterator<Item = &'static str>) -> Self {
+        self.url_schemes.extend(schemes);
+        self
+    }
+    /// Check if an element with this tag name is kept.
+    pub fn is_tag_allowed(&self, tag: &str) -> bool {
+        self.tags.contains(tag.to_ascii_lowercase().as_str())
+    }
+    /// Check if an attribute with this name and value is kept.
+    pub fn is_attribute_allowed(&self, name: &str, value: &str) -> bool {
+        let name = name.to_ascii_lowercase();
+        if name.starts_with("on") || name == "style" || !self.attributes.contains(name.as_str()) {
+            return false;
+        }
+        !URL_ATTRIBUTES.contains(&name.as_str()) || self.is_url_allowed(value)
+    }
+    /// Check if a URL is relative or has an allowed scheme.
+    pub fn is_url_allowed(&self, url: &str) -> bool {
+        // browsers ignore ASCII tabs and newlines anywhere in a URL,
+        // and leading/trailing control characters and spaces
+        let url: String = url
+            .trim_matches(|c: char| c <= ' ')
+            .chars()
+            .filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+            .collect();
+        let scheme_end = url.find([':', '/', '?', '#']);
+        match scheme_end {
+            Some(end) if url[end..].starts_with(':') => self
+                .url_schemes
+                .contains(url[..end].to_ascii_lowercase().as_str()),
+            _ => true,
+        }
+    }
+    fn sanitize_children(&self, parent: &Node) {
+        let mut current = parent.first_child();
+        while let Some(node) = current {
+            current = node.next_sibling();
+            match node.dyn_into::<Element>() {
+                Ok(element) => self.sanitize_element(parent, element),
+                Err(node) if node.node_type() == Node::TEXT_NODE => {}
+                Err(node) => {
+                    parent.remove_child(&node).unwrap_throw();
+                }
+            }
+        }
+    }
+    fn sanitize_element(&self, parent: &Node, element: Element) {
+        let tag = element.local_name();
+        if REMOVE_WITH_CONTENT.contains(&tag.as_str()) && !self.is_tag_allowed(&tag) {
+            parent.remove_child(&element).unwrap_throw();
+            return;
+        }
+        self.sanitize_children(&element);
+        if self.is_tag_allowed(&tag) {
+            for name in element.get_attribute_names().iter() {
+                let name = name.as_string().unwrap_throw();
+                let value = element.get_attribute(&name).unwrap_or_default();
+                if !self.is_attribute_allowed(&name, &value) {
+                    element.remove_attribute(&name).unwrap_throw();
+                }
+            }
+        } else {
+            while let Some(child) = element.first_child() {
+                parent.insert_before(&child, Some(&element)).unwrap_throw();
+            }
+            parent.remove_child(&element).unwrap_throw();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_schemes() {
+        let s = Sanitizer::default();
+        assert!(s.is_url_allowed("https://example.com"));
+        assert!(s.is_url_allowed("HTTP://example.com"));
+        assert!(s.is_url_allowed("mailto:a@example.com"));
+        assert!(s.is_url_allowed("/path/to:file"));
+        assert!(s.is_url_allowed("page?q=a:b"));
+        assert!(s.is_url_allowed("#top"));
+        assert!(!s.is_url_allowed("javascript:alert(1)"));
+        assert!(!s.is_url_allowed("  JavaScript:alert(1)"));
+        assert!(!s.is_url_allowed("java\tscript:alert(1)"));
+        assert!(!s.is_url_allowed("java\nscript:alert(1)"));
+        assert!(!s.is_url_allowed("\u{1}javascript:alert(1)"));
+        assert!(!s.is_url_allowed("data:text/html,<script>"));
+        assert!(!s.is_url_allowed("vbscript:msgbox"));
+    }
+
+    #[test]
+    fn attributes() {
+        let s = Sanitizer::default();
+        assert!(s.is_attribute_allowed("title", "javascript:x"));
+        assert!(s.is_attribute_allowed("HREF", "https://example.com"));
+        assert!(!s.is_attribute_allowed("href", "javascript:x"));
+        assert!(!s.is_attribute_allowed("onclick", "x()"));
+        assert!(!s.is_attribute_allowed("style", "color: red"));
+        assert!(!s.is_attribute_allowed("data-x", ""));
+        let s = s.allow_attributes(["onload", "style"]);
+        assert!(!s.is_attribute_allowed("onload", "x()"));
+        assert!(!s.is_attribute_allowed("style", "color: red"));
+    }
+
+    #[test]
+    fn tags() {
+        let s = Sanitizer::default();
+        assert!(s.is_tag_allowed("P"));
+        assert!(!s.is_tag_allowed("script"));
+        assert!(Sanitizer::empty()
+            .allow_tags(["details"])
+            .is_tag_allowed("details"));
+    }
+}
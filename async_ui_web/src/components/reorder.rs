@@ -0,0 +1,731 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    future::{pending, Future},
+    rc::{Rc, Weak},
+    task::{Poll, Waker},
+};
+
+use async_ui_web_core::ContainerNodeFuture;
+use async_ui_web_html::{events::EmitElementEvent, nodes::Div};
+use futures_lite::{Stream, StreamExt};
+use wasm_bindgen::UnwrapThrowExt;
+use web_sys::{HtmlElement, KeyboardEvent, Node};
+
+use crate::{
+    gestures::{EmitGesture, GesturePhase},
+    lists::ListModel,
+    observers::{MutationOptions, MutationStream, ObserveElement},
+    race,
+    style::{px, ShortcutStyle, Transform},
+};
+
+/// Thickness (in CSS pixels) of the line showing where the item will be dropped.
+const INDICATOR_WIDTH: f64 = 3.0;
+
+/**
+A list whose items can be reordered by the user.
+
+Items can be dragged with the mouse, touch, or a pen (using pointer events),
+or moved with the keyboard: focus an item, press <kbd>Space</kbd> or
+<kbd>Enter</kbd> to pick it up, use the arrow keys, <kbd>Home</kbd>, and
+<kbd>End</kbd> to move it, and press <kbd>Space</kbd>, <kbd>Enter</kbd>,
+or <kbd>Escape</kbd> to put it down.
+
+The component doesn't change the order of anything on its own.
+It reports the moves the user makes through [until_move][Reorder::until_move],
+and you update your data accordingly. With [ModeledList][crate::lists::ModeledList],
+use [ReorderMove::apply].
+
+```
+# use std::cell::RefCell;
+# use async_ui_web::{components::Reorder, join, lists::{ListModel, ModeledList}, prelude_traits::*};
+# use futures_lite::StreamExt;
+# let _ = async {
+let reorder = Reorder::new_vertical();
+let model = RefCell::new(ListModel::from(vec!["apple", "banana", "cherry"]));
+let list = ModeledList::new(|key: &&'static str| {
+    let key = *key;
+    reorder.wrap_item(key, key.render())
+});
+join((
+    reorder.render(list.render()),
+    async {
+        list.update(&model.borrow());
+        let mut moves = reorder.until_move();
+        while let Some(mv) = moves.next().await {
+            mv.apply(&mut model.borrow_mut());
+            list.update(&model.borrow());
+        }
+    },
+))
+.await;
+# };
+```
+
+To let items be dragged between lists, put the lists in the same [ReorderGroup].
+
+While an item is being dragged, its wrapper has the `data-reorder="dragging"`
+attribute. The drop position is shown with a `box-shadow` on the neighboring
+item, which also gets `data-reorder="drop-before"` or `data-reorder="drop-after"`
+(or on the list, with `data-reorder="drop-inside"`, if the list is empty).
+Use these attributes for your own styling.
+*/
+pub struct Reorder<K: Clone + PartialEq> {
+    group: ReorderGroup<K>,
+    list: Rc<List<K>>,
+}
+
+/// Lists that items can be dragged between.
+///
+/// ```
+/// # use async_ui_web::components::{Reorder, ReorderGroup};
+/// # let _ = async {
+/// let group = ReorderGroup::<u32>::new();
+/// let todo = Reorder::new_vertical_in(&group);
+/// let done = Reorder::new_vertical_in(&group);
+/// # };
+/// ```
+///
+/// Moves between the lists can be observed with [ReorderGroup::until_move].
+/// Use [ReorderMove::apply_between] to apply them to your data.
+///
+/// This is cheap to clone.
+pub struct ReorderGroup<K: Clone + PartialEq> {
+    inner: Rc<Group<K>>,
+}
+
+impl<K: Clone + PartialEq> Clone for ReorderGroup<K> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// Identifies a [Reorder] list within its [ReorderGroup].
+///
+/// Get it with [Reorder::id].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ListId(usize);
+
+/// A move made by the user, yielded by [Reorder::until_move] and
+/// [ReorderGroup::until_move].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReorderMove<K> {
+    /// The item moved.
+    pub key: K,
+    /// The list the item was in.
+    pub from: ListId,
+    /// The list the item was moved to. This is the same as `from` for moves
+    /// within a list.
+    pub to: ListId,
+    /// The item the moved item should now be right before,
+    /// or `None` if it should now be at the end of the list.
+    pub before: Option<K>,
+}
+
+struct Group<K> {
+    lists: RefCell<Vec<Rc<List<K>>>>,
+    next_id: Cell<usize>,
+    drag: RefCell<Option<Drag<K>>>,
+    subscribers: RefCell<Vec<Weak<Subscriber<K>>>>,
+}
+
+/// Moves waiting to be yielded by one [until_move][Group::until_move] stream.
+struct Subscriber<K> {
+    queue: RefCell<VecDeque<ReorderMove<K>>>,
+    waker: RefCell<Option<Waker>>,
+}
+
+struct List<K> {
+    id: ListId,
+    container: Div,
+    horizontal: bool,
+    items: RefCell<Vec<(K, HtmlElement)>>,
+}
+
+struct Drag<K> {
+    key: K,
+    from: ListId,
+    target: Option<DropTarget<K>>,
+}
+
+struct DropTarget<K> {
+    list: Rc<List<K>>,
+    before: Option<K>,
+    marked: Option<HtmlElement>,
+}
+
+impl<K: Clone + PartialEq> Default for ReorderGroup<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Clone + PartialEq> ReorderGroup<K> {
+    /// Create a new group with no list in it.
+    pub fn new() -> Self {
+        Self {
+            inner: Rc::new(Group {
+                lists: RefCell::new(Vec::new()),
+                next_id: Cell::new(0),
+                drag: RefCell::new(None),
+                subscribers: RefCell::new(Vec::new()),
+            }),
+        }
+    }
+    /// Get a [Stream] that yields every move made in any list in the group.
+    pub fn until_move(&self) -> impl Stream<Item = ReorderMove<K>> + '_ {
+        self.inner.until_move(|_| true)
+    }
+}
+
+impl<K: Clone + PartialEq> Reorder<K> {
+    /// Create a vertical list, in a group of its own.
+    pub fn new_vertical() -> Self {
+        Self::new_vertical_in(&ReorderGroup::new())
+    }
+    /// Create a horizontal list, in a group of its own.
+    pub fn new_horizontal() -> Self {
+        Self::new_horizontal_in(&ReorderGroup::new())
+    }
+    /// Create a vertical list in the given group.
+    pub fn new_vertical_in(group: &ReorderGroup<K>) -> Self {
+        Self::new_in(group, false)
+    }
+    /// Create a horizontal list in the given group.
+    pub fn new_horizontal_in(group: &ReorderGroup<K>) -> Self {
+        Self::new_in(group, true)
+    }
+    fn new_in(group: &ReorderGroup<K>, horizontal: bool) -> Self {
+        let id = group.inner.next_id.get();
+        group.inner.next_id.set(id + 1);
+        let list = Rc::new(List {
+            id: ListId(id),
+            container: Div::new(),
+            horizontal,
+            items: RefCell::new(Vec::new()),
+        });
+        group.inner.lists.borrow_mut().push(list.clone());
+        Self {
+            group: group.clone(),
+            list,
+        }
+    }
+    /// The ID of this list in its group.
+    pub fn id(&self) -> ListId {
+        self.list.id
+    }
+    /// The `<div>` containing the list.
+    pub fn container(&self) -> &Div {
+        &self.list.container
+    }
+    /// Render the list here.
+    ///
+    /// The argument Future should render the items, each wrapped with
+    /// [wrap_item][Self::wrap_item]. Usually, it is the `render()` of a
+    /// [ModeledList][crate::lists::ModeledList] or some other list component.
+    pub fn render<F: Future>(&self, items: F) -> ContainerNodeFuture<F> {
+        self.list.container.render(items)
+    }
+    /// Render an item of the list, inside a draggable `<div>`.
+    ///
+    /// The key identifies the item in the [ReorderMove]s reported;
+    /// each item in the group should have a different key.
+    ///
+    /// This Future completes when `content` completes.
+    pub async fn wrap_item<F: Future>(&self, key: K, content: F) {
+        let wrapper = Div::new();
+        let element: &HtmlElement = wrapper.as_ref();
+        element.set_tab_index(0);
+        element.set_attribute("role", "button").unwrap_throw();
+        element
+            .set_attribute("aria-roledescription", "sortable")
+            .unwrap_throw();
+        element
+            .set_attribute("aria-pressed", "false")
+            .unwrap_throw();
+        // let touch drags move the item instead of scrolling the page
+        element.set_style("touch-action", "none");
+        self.list
+            .items
+            .borrow_mut()
+            .push((key.clone(), element.clone()));
+        let _guard = scopeguard::guard((), |_| {
+            self.list
+                .items
+                .borrow_mut()
+                .retain(|(_, item)| item != element);
+        });
+        race((
+            wrapper.render(async {
+                content.await;
+            }),
+            self.pointer_drag(&key, element),
+            self.keyboard_reorder(&key, element),
+        ))
+        .await;
+    }
+    /// Get a [Stream] that yields moves into, out of, and within this list.
+    pub fn until_move(&self) -> impl Stream<Item = ReorderMove<K>> + '_ {
+        let id = self.list.id;
+        self.group
+            .inner
+            .until_move(move |mv| mv.from == id || mv.to == id)
+    }
+    async fn pointer_drag(&self, key: &K, element: &HtmlElement) {
+        let group = &self.group.inner;
+        let mut drags = element.until_drag_gesture();
+        while let Some(drag) = drags.next().await {
+            match drag.phase {
+                GesturePhase::Start => {
+                    *group.drag.borrow_mut() = Some(Drag {
+                        key: key.clone(),
+                        from: self.list.id,
+                        target: None,
+                    });
+                    element.set_style("position", "relative");
+                    element.set_style("z-index", 1);
+                    element
+                        .set_attribute("data-reorder", "dragging")
+                        .unwrap_throw();
+                }
+                GesturePhase::Move => {}
+                GesturePhase::End | GesturePhase::Cancel => {
+                    element.del_style("transform");
+                    element.del_style("position");
+                    element.del_style("z-index");
+                    element.remove_attribute("data-reorder").unwrap_throw();
+                    group.finish_drag(drag.phase == GesturePhase::End);
+                    continue;
+                }
+            }
+            element.set_style(
+                "transform",
+                Transform::new().translate(px(drag.dx), px(drag.dy)),
+            );
+            group.update_target(drag.x, drag.y);
+        }
+    }
+    async fn keyboard_reorder(&self, key: &K, element: &HtmlElement) {
+        let (back, forward) = if self.list.horizontal {
+            ("ArrowLeft", "ArrowRight")
+        } else {
+            ("ArrowUp", "ArrowDown")
+        };
+        let mut keydowns = element.until_keydown();
+        let mut focusouts = element.until_focusout();
+        let grabbed = Cell::new(false);
+        let set_grabbed = |value: bool| {
+            grabbed.set(value);
+            element
+                .set_attribute("aria-pressed", if value { "true" } else { "false" })
+                .unwrap_throw();
+        };
+        // the last keyboard move, until the list shows it
+        let mut pending_move: Option<(Option<K>, MutationStream)> = None;
+        loop {
+            let step = race((
+                async { Step::Key((&mut keydowns).await) },
+                async {
+                    (&mut focusouts).await;
+                    Step::FocusOut
+                },
+                async {
+                    let Some((before, mutations)) = &mut pending_move else {
+                        return pending().await;
+                    };
+                    while !is_noop(&self.list.ordered_keys(), key, before.as_ref()) {
+                        mutations.next().await;
+                    }
+                    Step::Moved
+                },
+            ))
+            .await;
+            let ev = match step {
+                Step::Key(ev) => ev,
+                // moving the element in the DOM makes it lose focus
+                Step::FocusOut if pending_move.is_some() => continue,
+                Step::FocusOut => {
+                    set_grabbed(false);
+                    continue;
+                }
+                Step::Moved => {
+                    pending_move = None;
+                    let _ = element.focus();
+                    focusouts = element.until_focusout();
+                    continue;
+                }
+            };
+            // ignore keys pressed in inputs and other things inside the item
+            if ev.target().as_ref() != Some(element.as_ref()) {
+                continue;
+            }
+            let key_name = ev.key();
+            match (grabbed.get(), key_name.as_str()) {
+                (_, " " | "Enter") => {
+                    ev.prevent_default();
+                    set_grabbed(!grabbed.get());
+                }
+                (true, "Escape") => set_grabbed(false),
+                (true, name) => {
+                    let order = self.list.ordered_keys();
+                    let Some(pos) = order.iter().position(|k| k == key) else {
+                        continue;
+                    };
+                    let before = match name {
+                        _ if name == back && pos > 0 => Some(order[pos - 1].clone()),
+                        _ if name == forward && pos + 1 < order.len() => {
+                            order.get(pos + 2).cloned()
+                        }
+                        "Home" if pos > 0 => Some(order[0].clone()),
+                        "End" if pos + 1 < order.len() => None,
+                        _ => continue,
+                    };
+                    ev.prevent_default();
+                    // watch the list before reporting the move, so that the
+                    // update is seen even if it happens right away;
+                    // focus is given back once the list shows the new order
+                    let mutations = self.list.container.until_mutation(&MutationOptions {
+                        child_list: true,
+                        subtree: true,
+                        ..Default::default()
+                    });
+                    pending_move = Some((before.clone(), mutations));
+                    self.group.inner.emit(ReorderMove {
+                        key: key.clone(),
+                        from: self.list.id,
+                        to: self.list.id,
+                        before,
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// What woke up [keyboard_reorder][Reorder::keyboard_reorder].
+enum Step {
+    Key(KeyboardEvent),
+    FocusOut,
+    /// The list now shows the last keyboard move.
+    Moved,
+}
+
+impl<K: Clone + PartialEq> Drop for Reorder<K> {
+    fn drop(&mut self) {
+        self.group
+            .inner
+            .lists
+            .borrow_mut()
+            .retain(|list| !Rc::ptr_eq(list, &self.list));
+    }
+}
+
+impl<K: Clone + PartialEq> Group<K> {
+    fn until_move<'a>(
+        &'a self,
+        filter: impl Fn(&ReorderMove<K>) -> bool + 'a,
+    ) -> impl Stream<Item = ReorderMove<K>> + 'a {
+        // each stream has its own queue, so that no move is missed
+        // even if several are made before the stream is polled
+        let subscriber = Rc::new(Subscriber {
+            queue: RefCell::new(VecDeque::new()),
+            waker: RefCell::new(None),
+        });
+        self.subscribers
+            .borrow_mut()
+            .push(Rc::downgrade(&subscriber));
+        futures_lite::stream::poll_fn(move |cx| loop {
+            let next = subscriber.queue.borrow_mut().pop_front();
+            match next {
+                Some(mv) if filter(&mv) => return Poll::Ready(Some(mv)),
+                Some(_) => continue,
+                None => {
+                    *subscriber.waker.borrow_mut() = Some(cx.waker().clone());
+                    return Poll::Pending;
+                }
+            }
+        })
+    }
+    fn emit(&self, mv: ReorderMove<K>) {
+        // drop the subscribers whose streams are gone
+        self.subscribers
+            .borrow_mut()
+            .retain(|subscriber| match subscriber.upgrade() {
+                Some(subscriber) => {
+                    subscriber.queue.borrow_mut().push_back(mv.clone());
+                    if let Some(waker) = subscriber.waker.borrow_mut().take() {
+                        waker.wake();
+                    }
+                    true
+                }
+                None => false,
+            });
+    }
+    fn update_target(&self, x: f64, y: f64) {
+        let mut drag = self.drag.borrow_mut();
+        let Some(drag) = drag.as_mut() else {
+            return;
+        };
+        let list = self
+            .lists
+            .borrow()
+            .iter()
+            .find(|list| {
+                let rect = list.container.get_bounding_client_rect();
+                (rect.left()..=rect.right()).contains(&x)
+                    && (rect.top()..=rect.bottom()).contains(&y)
+            })
+            .cloned();
+        let new_target = list.map(|list| {
+            let before = list.key_before_point(&drag.key, x, y);
+            (list, before)
+        });
+        let unchanged = match (&drag.target, &new_target) {
+            (Some(old), Some((list, before))) => {
+                Rc::ptr_eq(&old.list, list) && old.before == *before
+            }
+            (None, None) => true,
+            _ => false,
+        };
+        if unchanged {
+            return;
+        }
+        if let Some(DropTarget {
+            marked: Some(marked),
+            ..
+        }) = drag.target.take()
+        {
+            marked.del_style("box-shadow");
+            marked.remove_attribute("data-reorder").unwrap_throw();
+        }
+        drag.target = new_target.map(|(list, before)| {
+            let marked = list.mark_drop(drag, before.as_ref());
+            DropTarget {
+                list,
+                before,
+                marked,
+            }
+        });
+    }
+    fn finish_drag(&self, dropped: bool) {
+        let Some(drag) = self.drag.borrow_mut().take() else {
+            return;
+        };
+        let Some(target) = drag.target else {
+            return;
+        };
+        if let Some(marked) = &target.marked {
+            marked.del_style("box-shadow");
+            marked.remove_attribute("data-reorder").unwrap_throw();
+        }
+        let noop = target.list.id == drag.from
+            && is_noop(
+                &target.list.ordered_keys(),
+                &drag.key,
+                target.before.as_ref(),
+            );
+        if dropped && !noop {
+            self.emit(ReorderMove {
+                key: drag.key,
+                from: drag.from,
+                to: target.list.id,
+                before: target.before,
+            });
+        }
+    }
+}
+
+impl<K: Clone + PartialEq> List<K> {
+    /// The items, in the order they appear in the document.
+    fn ordered_items(&self) -> Vec<(K, HtmlElement)> {
+        let mut items = self.items.borrow().clone();
+        items.sort_by(|(_, a), (_, b)| {
+            if a.compare_document_position(b) & Node::DOCUMENT_POSITION_FOLLOWING != 0 {
+                std::cmp::Ordering::Less
+            } else {
+                std::cmp::Ordering::Greater
+            }
+        });
+        items
+    }
+    fn ordered_keys(&self) -> Vec<K> {
+        self.ordered_items()
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect()
+    }
+    /// Find the item that an item dropped at the given point should go before.
+    fn key_before_point(&self, dragged: &K, x: f64, y: f64) -> Option<K> {
+        self.ordered_items()
+            .into_iter()
+            .filter(|(key, _)| key != dragged)
+            .find(|(_, element)| {
+                let rect = element.get_bounding_client_rect();
+                if self.horizontal {
+                    x < rect.left() + rect.width() * 0.5
+                } else {
+                    y < rect.top() + rect.height() * 0.5
+                }
+            })
+            .map(|(key, _)| key)
+    }
+    /// Show where the dragged item would go. Returns the element marked.
+    fn mark_drop(&self, drag: &Drag<K>, before: Option<&K>) -> Option<HtmlElement> {
+        let items = self.ordered_items();
+        if drag.from == self.id && is_noop(&self.ordered_keys(), &drag.key, before) {
+            return None;
+        }
+        let axis = self.horizontal as usize ^ 1;
+        let shadow = |sign: f64| {
+            let mut offsets = [0.0; 2];
+            offsets[axis] = sign * INDICATOR_WIDTH;
+            format!("{}px {}px 0 0 Highlight", offsets[0], offsets[1])
+        };
+        let others = || items.iter().filter(|(key, _)| *key != drag.key);
+        let (element, value, shadow) = match before {
+            Some(before) => {
+                let (_, element) = items.iter().find(|(key, _)| key == before)?;
+                (element.clone(), "drop-before", shadow(-1.0))
+            }
+            None => match others().last() {
+                Some((_, element)) => (element.clone(), "drop-after", shadow(1.0)),
+                None => (
+                    AsRef::<HtmlElement>::as_ref(&self.container).clone(),
+                    "drop-inside",
+                    format!("inset 0 0 0 {INDICATOR_WIDTH}px Highlight"),
+                ),
+            },
+        };
+        element.set_style("box-shadow", shadow);
+        element.set_attribute("data-reorder", value).unwrap_throw();
+        Some(element)
+    }
+}
+
+impl<K: Clone + PartialEq> ReorderMove<K> {
+    /// Apply a move within a list to the model of that list.
+    ///
+    /// Does nothing if the moved item is not in the model.
+    /// If `before` is not in the model, the item is moved to the end.
+    pub fn apply(&self, model: &mut ListModel<K>) {
+        let Some(from) = model.iter().position(|key| *key == self.key) else {
+            return;
+        };
+        let mut to = self
+            .before
+            .as_ref()
+            .and_then(|before| model.iter().position(|key| key == before))
+            .unwrap_or(model.len());
+        if to > from {
+            to -= 1;
+        }
+        if from != to {
+            model.move_item(from, to);
+        }
+    }
+    /// Apply a move between lists by removing the item from the `from` model
+    /// and inserting it into the `to` model.
+    ///
+    /// Does nothing if the moved item is not in the `from` model.
+    /// If `before` is not in the `to` model, the item is inserted at the end.
+    pub fn apply_between(&self, from: &mut ListModel<K>, to: &mut ListModel<K>) {
+        let Some(index) = from.iter().position(|key| *key == self.key) else {
+            return;
+        };
+        let key = from.remove(index);
+        let at = self
+            .before
+            .as_ref()
+            .and_then(|before| to.iter().position(|key| key == before))
+            .unwrap_or(to.len());
+        to.insert(at, key);
+    }
+}
+
+/// Whether putting `key` before `before` leaves the order unchanged.
+fn is_noop<K: PartialEq>(order: &[K], key: &K, before: Option<&K>) -> bool {
+    let Some(pos) = order.iter().position(|k| k == key) else {
+        return false;
+    };
+    match before {
+        Some(before) => before == key || order.get(pos + 1) == Some(before),
+        None => pos + 1 == order.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mv(key: char, before: Option<char>) -> ReorderMove<char> {
+        ReorderMove {
+            key,
+            from: ListId(0),
+            to: ListId(0),
+            before,
+        }
+    }
+
+    #[test]
+    fn apply_within_list() {
+        let mut model = ListModel::from(vec!['a', 'b', 'c', 'd']);
+        mv('a', Some('c')).apply(&mut model);
+        assert_eq!(&*model, &['b', 'a', 'c', 'd']);
+        mv('d', Some('b')).apply(&mut model);
+        assert_eq!(&*model, &['d', 'b', 'a', 'c']);
+        mv('d', None).apply(&mut model);
+        assert_eq!(&*model, &['b', 'a', 'c', 'd']);
+        mv('c', Some('d')).apply(&mut model);
+        assert_eq!(&*model, &['b', 'a', 'c', 'd']);
+    }
+
+    #[test]
+    fn apply_between_lists() {
+        let mut from = ListModel::from(vec!['a', 'b']);
+        let mut to = ListModel::from(vec!['x', 'y']);
+        mv('a', Some('y')).apply_between(&mut from, &mut to);
+        assert_eq!(&*from, &['b']);
+        assert_eq!(&*to, &['x', 'a', 'y']);
+        mv('b', None).apply_between(&mut from, &mut to);
+        assert!(from.is_empty());
+        assert_eq!(&*to, &['x', 'a', 'y', 'b']);
+    }
+
+    #[test]
+    fn no_move_missed() {
+        let group = ReorderGroup::<char>::new();
+        let mut all = group.inner.until_move(|_| true);
+        let mut from_one = group.inner.until_move(|mv| mv.from == ListId(1));
+        let other = ReorderMove {
+            from: ListId(1),
+            ..mv('x', None)
+        };
+        group.inner.emit(mv('a', Some('b')));
+        group.inner.emit(other.clone());
+        group.inner.emit(mv('c', None));
+        futures_lite::future::block_on(async {
+            assert_eq!(all.next().await, Some(mv('a', Some('b'))));
+            assert_eq!(all.next().await, Some(other.clone()));
+            assert_eq!(all.next().await, Some(mv('c', None)));
+            assert_eq!(from_one.next().await, Some(other));
+        });
+        assert_eq!(
+            futures_lite::future::block_on(futures_lite::future::poll_once(all.next())),
+            None
+        );
+    }
+
+    #[test]
+    fn noop_moves() {
+        let order = ['a', 'b', 'c'];
+        assert!(is_noop(&order, &'a', Some(&'b')));
+        assert!(is_noop(&order, &'b', Some(&'b')));
+        assert!(is_noop(&order, &'c', None));
+        assert!(!is_noop(&order, &'a', Some(&'c')));
+        assert!(!is_noop(&order, &'a', None));
+    }
+}
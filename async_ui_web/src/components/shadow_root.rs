@@ -0,0 +1,102 @@
+use std::{cell::RefCell, collections::HashMap, future::Future};
+
+use async_ui_web_core::{window::DOCUMENT, ContainerNodeFuture};
+use js_sys::Array;
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::{CssStyleSheet, Element, HtmlStyleElement, ShadowRootInit, ShadowRootMode};
+
+thread_local! {
+    /// Constructed stylesheets, by CSS text, so that shadow roots using the
+    /// same styles share one stylesheet object.
+    static SHEETS: RefCell<HashMap<&'static str, CssStyleSheet>> = RefCell::new(HashMap::new());
+}
+
+/**
+A [shadow root](https://developer.mozilla.org/en-US/docs/Web/API/ShadowRoot),
+for isolating the styles of a widget from the rest of the page.
+
+Styles from the page don't apply inside the shadow root,
+and styles added to the shadow root don't apply outside.
+
+```
+# use async_ui_web::{components::ShadowRoot, html::{Div, Button}, prelude_traits::*};
+mod style {
+    async_ui_web::css!(
+        shadow,
+        "
+.button {
+    color: red;
+}
+        "
+    );
+}
+# let _ = async {
+let host = Div::new();
+let shadow = ShadowRoot::attach(&host);
+shadow.add_stylesheet(style::STYLESHEET);
+host.render(shadow.render(
+    Button::new().with_class(style::button).render("isolated".render())
+))
+.await;
+# };
+```
+*/
+pub struct ShadowRoot {
+    pub root: web_sys::ShadowRoot,
+}
+
+impl ShadowRoot {
+    /// Attach an `open` shadow root to the host element.
+    ///
+    /// The element must be one that supports shadow roots (such as a `<div>`,
+    /// a `<span>`, or a custom element), and must not have one already.
+    pub fn attach(host: &impl AsRef<Element>) -> Self {
+        Self::attach_with_mode(host, ShadowRootMode::Open)
+    }
+    /// Attach a shadow root with the given mode to the host element.
+    pub fn attach_with_mode(host: &impl AsRef<Element>, mode: ShadowRootMode) -> Self {
+        Self {
+            root: host
+                .as_ref()
+                .attach_shadow(&ShadowRootInit::new(mode))
+                .unwrap_throw(),
+        }
+    }
+    /// Add CSS (usually the `STYLESHEET` constant generated by [css!][crate::css])
+    /// to this shadow root.
+    ///
+    /// Constructable stylesheets are used where supported, so that many shadow
+    /// roots with the same styles share one parsed stylesheet.
+    /// Elsewhere, a `<style>` element is added to the shadow root.
+    pub fn add_stylesheet(&self, css: &'static str) {
+        let sheet = SHEETS.with(|sheets| {
+            if let Some(sheet) = sheets.borrow().get(css) {
+                return Some(sheet.clone());
+            }
+            let sheet = CssStyleSheet::new().ok()?;
+            sheet.replace_sync(css).ok()?;
+            sheets.borrow_mut().insert(css, sheet.clone());
+            Some(sheet)
+        });
+        match sheet {
+            Some(sheet) => {
+                let sheets = Array::from(&self.root.adopted_style_sheets());
+                sheets.push(&sheet);
+                self.root.set_adopted_style_sheets(&sheets);
+            }
+            None => {
+                let style: HtmlStyleElement = DOCUMENT
+                    .with(|doc| doc.create_element("style").unwrap_throw().unchecked_into());
+                style.set_inner_html(css);
+                self.root.prepend_with_node_1(&style).unwrap_throw();
+            }
+        }
+    }
+    /// Render the given future inside the shadow root.
+    ///
+    /// Anything the argument Future renders will appear in the shadow root.
+    /// The host element itself must be rendered separately.
+    pub fn render<F: Future>(&self, c: F) -> ContainerNodeFuture<F> {
+        ContainerNodeFuture::new_root(c, self.root.clone().into())
+    }
+}
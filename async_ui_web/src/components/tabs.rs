@@ -0,0 +1,448 @@
+use std::{
+    cell::RefCell,
+    future::{pending, Future},
+    hash::Hash,
+    pin::Pin,
+    rc::Rc,
+};
+
+use async_ui_web_html::{
+    events::EmitElementEvent,
+    nodes::{Button, Div},
+};
+use futures_lite::{FutureExt, StreamExt};
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::Node;
+
+use crate::{
+    accessibility::ShortcutAria,
+    focus::{roving_next, Orientation},
+    join,
+    lists::DynamicList,
+    race,
+    shortcut_traits::ShortcutRenderStr,
+    ReactiveCell,
+};
+
+type PanelFactory = Rc<dyn Fn() -> Pin<Box<dyn Future<Output = ()>>>>;
+type ItemFuture<'a> = Pin<Box<dyn Future<Output = ()> + 'a>>;
+
+/// When the content of a [Tabs] panel is created and dropped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PanelMode {
+    /// Create the panel content when its tab is selected, and drop it when
+    /// another tab is selected. Its state is lost in between.
+    Lazy,
+    /// Create the panel content when its tab is first selected, and keep it
+    /// (hidden) when another tab is selected. Its state is preserved.
+    KeepAlive,
+}
+
+struct Tab<K> {
+    key: K,
+    label: String,
+    mode: PanelMode,
+    panel: PanelFactory,
+    button: Button,
+    panel_div: Div,
+}
+
+/**
+A set of tabs, each showing a panel.
+
+This follows the [ARIA tabs pattern](https://www.w3.org/WAI/ARIA/apg/patterns/tabs/):
+the tabs are in a `tablist`, <kbd>Tab</kbd> moves focus to the selected tab,
+and the arrow keys (plus <kbd>Home</kbd> and <kbd>End</kbd>) select other tabs.
+
+Each tab has a key of type `K`. The selected tab is in a [ReactiveCell];
+watch it to react to the user switching tabs, and set it to switch tabs yourself.
+
+```
+# use async_ui_web::{components::{PanelMode, Tabs}, prelude_traits::*};
+# async fn edit_profile() {}
+# async fn account_history() {}
+# let _ = async {
+let tabs = Tabs::new();
+tabs.add_tab("profile", "Profile", PanelMode::KeepAlive, edit_profile);
+tabs.add_tab("history", "History", PanelMode::Lazy, account_history);
+// the first tab added is selected; select another one
+tabs.set_selected("history");
+tabs.render().await;
+# };
+```
+*/
+pub struct Tabs<K: Eq + Hash + Clone + 'static> {
+    tab_list: Div,
+    tabs: RefCell<Vec<Rc<Tab<K>>>>,
+    /// Changed whenever tabs are added or removed.
+    tabs_changed: ReactiveCell<()>,
+    selected: ReactiveCell<Option<K>>,
+}
+
+impl<K: Eq + Hash + Clone + 'static> Default for Tabs<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Eq + Hash + Clone + 'static> Tabs<K> {
+    /// Create an empty set of tabs.
+    pub fn new() -> Self {
+        let tab_list = Div::new();
+        tab_list.set_attribute("role", "tablist").unwrap_throw();
+        Self {
+            tab_list,
+            tabs: RefCell::new(Vec::new()),
+            tabs_changed: ReactiveCell::new(()),
+            selected: ReactiveCell::new(None),
+        }
+    }
+    /// The `<div role="tablist">` that the tab buttons are in.
+    ///
+    /// Set `aria-label` on this to describe the tabs.
+    pub fn tab_list(&self) -> &Div {
+        &self.tab_list
+    }
+    /// Add a tab at the end. `panel` is called to create the panel content,
+    /// as set by `mode`.
+    ///
+    /// If no tab is selected, the new tab is selected.
+    pub fn add_tab<F: Future<Output = ()> + 'static>(
+        &self,
+        key: K,
+        label: &str,
+        mode: PanelMode,
+        panel: impl Fn() -> F + 'static,
+    ) {
+        let button = Button::new();
+        button.set_attribute("role", "tab").unwrap_throw();
+        button.set_type("button");
+        let panel_div = Div::new();
+        panel_div.set_attribute("role", "tabpanel").unwrap_throw();
+        panel_div.set_tab_index(0);
+        button
+            .set_attribute("aria-controls", &panel_div.aria_id())
+            .unwrap_throw();
+        panel_div.add_aria_labelledby(&button);
+        self.tabs.borrow_mut().push(Rc::new(Tab {
+            key: key.clone(),
+            label: label.to_owned(),
+            mode,
+            panel: Rc::new(move || Box::pin(panel())),
+            button,
+            panel_div,
+        }));
+        if self.selected.borrow().is_none() {
+            *self.selected.borrow_mut() = Some(key);
+        }
+        *self.tabs_changed.borrow_mut() = ();
+    }
+    /// Remove a tab. If it was selected, the first remaining tab is selected.
+    pub fn remove_tab(&self, key: &K) {
+        self.tabs.borrow_mut().retain(|tab| &tab.key != key);
+        let selected = selection_after_remove(
+            self.selected.borrow().as_ref(),
+            key,
+            self.tabs.borrow().iter().map(|tab| &tab.key),
+        );
+        if selected != *self.selected.borrow() {
+            *self.selected.borrow_mut() = selected;
+        }
+        *self.tabs_changed.borrow_mut() = ();
+    }
+    /// The key of the selected tab.
+    ///
+    /// Use [until_change][ReactiveCell::until_change] on this to know when
+    /// the user selects a tab. Set it to select a tab.
+    pub fn selected(&self) -> &ReactiveCell<Option<K>> {
+        &self.selected
+    }
+    /// Select the tab with the given key.
+    pub fn set_selected(&self, key: K) {
+        *self.selected.borrow_mut() = Some(key);
+    }
+    /// Render the tab list, followed by the panels.
+    ///
+    /// This async method never completes.
+    pub async fn render(&self) {
+        let buttons = DynamicList::<K, ItemFuture>::new();
+        let panels = DynamicList::<K, ItemFuture>::new();
+        join((
+            self.tab_list.render(buttons.render()),
+            panels.render(),
+            async {
+                let mut shown = Vec::new();
+                let mut tabs_changes = self.tabs_changed.until_change();
+                let mut selected_changes = self.selected.until_change();
+                let mut keydowns = self.tab_list.until_keydown();
+                let mut clicks = self.tab_list.until_click();
+                loop {
+                    self.sync_lists(&buttons, &panels, &mut shown);
+                    self.sync_attributes();
+                    race((
+                        async {
+                            tabs_changes.next().await;
+                        },
+                        async {
+                            selected_changes.next().await;
+                        },
+                        async {
+                            let ev = (&mut keydowns).await;
+                            if ev.ctrl_key() || ev.alt_key() || ev.meta_key() {
+                                return;
+                            }
+                            let tabs = self.tabs.borrow().clone();
+                            let Some(current) = self.selected_index(&tabs) else {
+                                return;
+                            };
+                            let next = roving_next(
+                                current,
+                                tabs.len(),
+                                &ev.key(),
+                                Orientation::Horizontal,
+                                true,
+                            );
+                            if let Some(next) = next {
+                                ev.prevent_default();
+                                self.set_selected(tabs[next].key.clone());
+                                let _ = tabs[next].button.focus();
+                            }
+                        },
+                        async {
+                            let ev = (&mut clicks).await;
+                            let target = ev.target().and_then(|t| t.dyn_into::<Node>().ok());
+                            let clicked = self
+                                .tabs
+                                .borrow()
+                                .iter()
+                                .find(|tab| tab.button.contains(target.as_ref()))
+                                .map(|tab| tab.key.clone());
+                            if let Some(key) = clicked {
+                                self.set_selected(key);
+                            }
+                        },
+                    ))
+                    .await;
+                }
+            },
+        ))
+        .await;
+    }
+    fn selected_index(&self, tabs: &[Rc<Tab<K>>]) -> Option<usize> {
+        let selected = self.selected.borrow();
+        tabs.iter()
+            .position(|tab| Some(&tab.key) == selected.as_ref())
+    }
+    /// Add and remove buttons and panels to match the tabs.
+    ///
+    /// Tabs are tracked by identity, so a tab that was removed and added
+    /// again with the same key gets a fresh button and panel.
+    fn sync_lists<'a>(
+        &'a self,
+        buttons: &DynamicList<'a, K, ItemFuture<'a>>,
+        panels: &DynamicList<'a, K, ItemFuture<'a>>,
+        shown: &mut Vec<Rc<Tab<K>>>,
+    ) {
+        let tabs = self.tabs.borrow().clone();
+        let (removed, added) = sync_by_identity(shown, &tabs);
+        for tab in removed {
+            buttons.remove(&tab.key);
+            panels.remove(&tab.key);
+        }
+        for tab in added {
+            let button_tab = tab.clone();
+            buttons.insert(
+                tab.key.clone(),
+                Box::pin(async move {
+                    button_tab
+                        .button
+                        .render(button_tab.label.as_str().render())
+                        .await
+                }),
+                None,
+            );
+            panels.insert(
+                tab.key.clone(),
+                Box::pin(async move {
+                    tab.panel_div
+                        .render(panel_content(
+                            &self.selected,
+                            &tab.key,
+                            tab.mode,
+                            &tab.panel,
+                        ))
+                        .await;
+                }),
+                None,
+            );
+        }
+    }
+    fn sync_attributes(&self) {
+        let tabs = self.tabs.borrow();
+        let selected = self.selected_index(&tabs);
+        for (index, tab) in tabs.iter().enumerate() {
+            let is_selected = Some(index) == selected;
+            tab.button
+                .set_attribute("aria-selected", if is_selected { "true" } else { "false" })
+                .unwrap_throw();
+            // only the selected tab is reachable with the Tab key
+            tab.button
+                .set_tab_index(if is_selected || (selected.is_none() && index == 0) {
+                    0
+                } else {
+                    -1
+                });
+            tab.panel_div.set_hidden(!is_selected);
+        }
+    }
+}
+
+/// Update `mounted` to hold the same items as `current`, compared by identity.
+/// Returns the items that went away and the items that are new, in order.
+fn sync_by_identity<T>(mounted: &mut Vec<Rc<T>>, current: &[Rc<T>]) -> (Vec<Rc<T>>, Vec<Rc<T>>) {
+    let (kept, removed) = std::mem::take(mounted)
+        .into_iter()
+        .partition(|item| current.iter().any(|c| Rc::ptr_eq(c, item)));
+    *mounted = kept;
+    let added: Vec<_> = current
+        .iter()
+        .filter(|item| !mounted.iter().any(|m| Rc::ptr_eq(m, item)))
+        .cloned()
+        .collect();
+    mounted.extend(added.iter().cloned());
+    (removed, added)
+}
+
+/// The selection after the tab with key `removed` is removed:
+/// the first remaining tab if it was selected, or else unchanged.
+fn selection_after_remove<'k, K: PartialEq + Clone + 'k>(
+    selected: Option<&K>,
+    removed: &K,
+    mut remaining: impl Iterator<Item = &'k K>,
+) -> Option<K> {
+    if selected == Some(removed) {
+        remaining.next().cloned()
+    } else {
+        selected.cloned()
+    }
+}
+
+/// Create, keep, or drop the content of a panel, as the selection changes.
+async fn panel_content<K: PartialEq>(
+    selected: &ReactiveCell<Option<K>>,
+    key: &K,
+    mode: PanelMode,
+    panel: &PanelFactory,
+) {
+    let is_selected = || selected.borrow().as_ref() == Some(key);
+    let until_selected = |want: bool| async move {
+        let mut changes = selected.until_change();
+        while is_selected() != want {
+            changes.next().await;
+        }
+    };
+    match mode {
+        PanelMode::KeepAlive => {
+            until_selected(true).await;
+            panel().await;
+            pending::<()>().await;
+        }
+        // only the panel renders anything, so it doesn't need a positioned `race`
+        PanelMode::Lazy => loop {
+            until_selected(true).await;
+            async {
+                panel().await;
+                pending::<()>().await;
+            }
+            .or(until_selected(false))
+            .await;
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use futures_lite::future::{block_on, poll_once};
+
+    use super::*;
+
+    #[test]
+    fn identity() {
+        let (a, b) = (Rc::new('a'), Rc::new('b'));
+        let mut mounted = Vec::new();
+        let (removed, added) = sync_by_identity(&mut mounted, &[a.clone(), b.clone()]);
+        assert!(removed.is_empty());
+        assert_eq!(added, [a.clone(), b.clone()]);
+
+        // `b` is removed, then added again with the same key
+        let new_b = Rc::new('b');
+        let (removed, added) = sync_by_identity(&mut mounted, &[a.clone(), new_b.clone()]);
+        assert!(Rc::ptr_eq(&removed[0], &b) && removed.len() == 1);
+        assert!(Rc::ptr_eq(&added[0], &new_b) && added.len() == 1);
+        assert!(Rc::ptr_eq(&mounted[0], &a) && Rc::ptr_eq(&mounted[1], &new_b));
+
+        let (removed, added) = sync_by_identity(&mut mounted, &[a.clone(), new_b]);
+        assert!(removed.is_empty() && added.is_empty());
+    }
+
+    #[test]
+    fn selection_fallback() {
+        let after = |selected: Option<char>, removed: char, remaining: &[char]| {
+            selection_after_remove(selected.as_ref(), &removed, remaining.iter())
+        };
+        // the selected tab is removed, so the first remaining one is selected
+        assert_eq!(after(Some('b'), 'b', &['a', 'c']), Some('a'));
+        // another tab is removed
+        assert_eq!(after(Some('c'), 'a', &['b', 'c']), Some('c'));
+        // the last tab is removed
+        assert_eq!(after(Some('a'), 'a', &[]), None);
+        assert_eq!(after(None, 'a', &['b']), None);
+    }
+
+    /// Counts how many times the panel content was created and dropped.
+    fn counting_panel(created: &Rc<Cell<u32>>, dropped: &Rc<Cell<u32>>) -> PanelFactory {
+        let (created, dropped) = (created.clone(), dropped.clone());
+        Rc::new(move || {
+            created.set(created.get() + 1);
+            let dropped = dropped.clone();
+            Box::pin(async move {
+                let _guard = scopeguard::guard((), |_| dropped.set(dropped.get() + 1));
+                pending::<()>().await;
+            })
+        })
+    }
+
+    fn lifecycle(mode: PanelMode) -> Vec<(u32, u32)> {
+        let (created, dropped) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(0)));
+        let panel = counting_panel(&created, &dropped);
+        let selected = ReactiveCell::new(Some('a'));
+        let mut content = Box::pin(panel_content(&selected, &'b', mode, &panel));
+        let mut counts = Vec::new();
+        for select in ['a', 'b', 'a', 'b'] {
+            *selected.borrow_mut() = Some(select);
+            block_on(poll_once(&mut content));
+            counts.push((created.get(), dropped.get()));
+        }
+        drop(content);
+        counts.push((created.get(), dropped.get()));
+        counts
+    }
+
+    #[test]
+    fn lazy_panel() {
+        assert_eq!(
+            lifecycle(PanelMode::Lazy),
+            [(0, 0), (1, 0), (1, 1), (2, 1), (2, 2)]
+        );
+    }
+
+    #[test]
+    fn keep_alive_panel() {
+        assert_eq!(
+            lifecycle(PanelMode::KeepAlive),
+            [(0, 0), (1, 0), (1, 0), (1, 0), (1, 1)]
+        );
+    }
+}
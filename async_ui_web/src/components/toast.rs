@@ -0,0 +1,429 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    future::{pending, poll_fn, Future},
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+use async_ui_web_html::{
+    events::{EmitElementEvent, EmitEvent},
+    nodes::{Button, Div},
+};
+use gloo_timers::future::TimeoutFuture;
+use web_sys::HtmlElement;
+
+use super::canvas::AnimationFrame;
+use crate::{
+    join, lists::DynamicList, race, shortcut_traits::ShortcutRenderStr, style::ShortcutStyle,
+};
+
+/// How long toasts stay by default.
+const DEFAULT_DURATION: Duration = Duration::from_secs(5);
+/// Length of the enter and leave transitions, in milliseconds.
+const TRANSITION_MS: u32 = 200;
+/// The least time a toast stays after the pointer leaves it, in milliseconds.
+const MIN_AFTER_HOVER_MS: f64 = 1000.0;
+
+type ToastFuture = Pin<Box<dyn Future<Output = ()>>>;
+
+thread_local! {
+    static QUEUE: Rc<Queue> = Rc::new(Queue::default());
+}
+
+/// Toasts waiting to be shown, and toasts done being shown.
+#[derive(Default)]
+struct Queue {
+    next_id: Cell<u64>,
+    waiting: RefCell<VecDeque<(u64, ToastFuture)>>,
+    finished: RefCell<Vec<u64>>,
+    waker: RefCell<Option<Waker>>,
+}
+
+impl Queue {
+    fn wake_host(&self) {
+        if let Some(waker) = self.waker.borrow_mut().take() {
+            waker.wake();
+        }
+    }
+}
+
+/**
+Shows the toasts created with [toast].
+
+Render one of these once, near the root of your app. Toasts created before it
+is rendered are shown once it is.
+
+The toasts stack up in the bottom-right corner of the page, newest at the bottom.
+Each toast is a `<div>` with a `data-toast-state` attribute that is
+`entering`, `open`, or `leaving`, which you can use for styling.
+
+```
+# use async_ui_web::{components::ToastHost, join};
+# async fn my_app() {}
+# let _ = async {
+let host = ToastHost::new();
+join((my_app(), host.render())).await;
+# };
+```
+*/
+pub struct ToastHost {
+    container: Div,
+    max_visible: Cell<usize>,
+}
+
+impl Default for ToastHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ToastHost {
+    /// Create a toast host.
+    pub fn new() -> Self {
+        let container = Div::new();
+        container.set_attribute("role", "region").ok();
+        container.set_attribute("aria-label", "Notifications").ok();
+        container.set_attribute("aria-live", "polite").ok();
+        for (name, value) in [
+            ("position", "fixed"),
+            ("right", "1rem"),
+            ("bottom", "1rem"),
+            ("z-index", "1000"),
+            ("display", "flex"),
+            ("flex-direction", "column"),
+            ("align-items", "flex-end"),
+            ("gap", "0.5rem"),
+        ] {
+            container.set_style(name, value);
+        }
+        Self {
+            container,
+            max_visible: Cell::new(5),
+        }
+    }
+    /// The `<div>` that the toasts are in. Use this to change where they go.
+    pub fn container(&self) -> &Div {
+        &self.container
+    }
+    /// How many toasts can be on screen at once. More toasts wait for their turn.
+    /// The default is 5.
+    pub fn set_max_visible(&self, max: usize) {
+        self.max_visible.set(max.max(1));
+    }
+    /// Render the host.
+    ///
+    /// This async method never completes.
+    pub async fn render(&self) {
+        let queue = QUEUE.with(Rc::clone);
+        let list = DynamicList::<u64, ToastFuture>::new();
+        let mut visible = 0;
+        join((self.container.render(list.render()), async {
+            loop {
+                poll_fn(|cx| {
+                    let can_show =
+                        visible < self.max_visible.get() && !queue.waiting.borrow().is_empty();
+                    if can_show || !queue.finished.borrow().is_empty() {
+                        Poll::Ready(())
+                    } else {
+                        *queue.waker.borrow_mut() = Some(cx.waker().clone());
+                        Poll::Pending
+                    }
+                })
+                .await;
+                for id in queue.finished.borrow_mut().drain(..) {
+                    if list.remove(&id) {
+                        visible -= 1;
+                    }
+                }
+                while visible < self.max_visible.get() {
+                    let Some((id, future)) = queue.waiting.borrow_mut().pop_front() else {
+                        break;
+                    };
+                    list.insert(id, future, None);
+                    visible += 1;
+                }
+            }
+        }))
+        .await;
+    }
+}
+
+/**
+Options for a [toast].
+
+`A` is the type of value its action buttons resolve to.
+For a toast without actions, write `ToastOptions::<()>::new()`;
+Rust doesn't fall back to the default `()` when inferring it.
+
+```
+# use std::time::Duration;
+# use async_ui_web::components::ToastOptions;
+#[derive(Clone)]
+enum Undo { Undo }
+let options = ToastOptions::new()
+    .duration(Some(Duration::from_secs(10)))
+    .action("Undo", Undo::Undo);
+```
+*/
+pub struct ToastOptions<A = ()> {
+    duration: Option<Duration>,
+    actions: Vec<(String, A)>,
+    dismissible: bool,
+}
+
+impl<A> Default for ToastOptions<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A> ToastOptions<A> {
+    /// The default options: the toast goes away after 5 seconds,
+    /// has a close button, and has no actions.
+    pub fn new() -> Self {
+        Self {
+            duration: Some(DEFAULT_DURATION),
+            actions: Vec::new(),
+            dismissible: true,
+        }
+    }
+    /// How long the toast stays, or `None` to keep it until the user closes it.
+    ///
+    /// The timer is paused while the pointer is over the toast.
+    pub fn duration(mut self, duration: Option<Duration>) -> Self {
+        self.duration = duration;
+        self
+    }
+    /// Add an action button. Clicking it closes the toast, and resolves the
+    /// [ToastHandle] with the given value.
+    pub fn action(mut self, label: impl Into<String>, value: A) -> Self {
+        self.actions.push((label.into(), value));
+        self
+    }
+    /// Whether to show a close button. The default is true.
+    pub fn dismissible(mut self, dismissible: bool) -> Self {
+        self.dismissible = dismissible;
+        self
+    }
+}
+
+/// Show a toast, with the given content, in the [ToastHost].
+///
+/// This can be called from anywhere, including spawned tasks.
+/// The toast is shown even if the returned [ToastHandle] is not awaited.
+///
+/// ```
+/// # use async_ui_web::{components::{toast, ToastOptions}, prelude_traits::*};
+/// # fn restore_item() {}
+/// # let _ = async {
+/// toast("Saved".render(), ToastOptions::<()>::new());
+///
+/// let undo = toast(
+///     "Item deleted".render(),
+///     ToastOptions::new().action("Undo", ()),
+/// );
+/// if undo.await.is_some() {
+///     restore_item();
+/// }
+/// # };
+/// ```
+pub fn toast<A: 'static>(
+    content: impl Future<Output = ()> + 'static,
+    options: ToastOptions<A>,
+) -> ToastHandle<A> {
+    let outcome = Rc::new(RefCell::new(Outcome {
+        result: None,
+        waker: None,
+    }));
+    QUEUE.with(|queue| {
+        let id = queue.next_id.replace(queue.next_id.get() + 1);
+        let future = show_toast(id, queue.clone(), content, options, outcome.clone());
+        queue.waiting.borrow_mut().push_back((id, Box::pin(future)));
+        queue.wake_host();
+    });
+    ToastHandle { outcome }
+}
+
+struct Outcome<A> {
+    /// `Some` once the toast is closed.
+    result: Option<Option<A>>,
+    waker: Option<Waker>,
+}
+
+/// Future returned by [toast].
+///
+/// It resolves once the toast closes: with `Some` and the value of the action
+/// if the user clicked an action button, and with `None` otherwise.
+pub struct ToastHandle<A> {
+    outcome: Rc<RefCell<Outcome<A>>>,
+}
+
+impl<A> Future for ToastHandle<A> {
+    type Output = Option<A>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut outcome = self.outcome.borrow_mut();
+        match outcome.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                outcome.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+async fn show_toast<A>(
+    id: u64,
+    queue: Rc<Queue>,
+    content: impl Future<Output = ()>,
+    options: ToastOptions<A>,
+    outcome: Rc<RefCell<Outcome<A>>>,
+) {
+    let item = Div::new();
+    let element: &HtmlElement = &item;
+    let set_state = |state: &str| {
+        element.set_attribute("data-toast-state", state).ok();
+        let open = state == "open";
+        element.set_style("opacity", if open { "1" } else { "0" });
+        element.set_style(
+            "transform",
+            if open { "none" } else { "translateY(0.5rem)" },
+        );
+    };
+    element.set_style(
+        "transition",
+        format_args!("opacity {TRANSITION_MS}ms, transform {TRANSITION_MS}ms"),
+    );
+    set_state("entering");
+    let ToastOptions {
+        duration,
+        actions,
+        dismissible,
+    } = options;
+    let action_buttons: Vec<(Button, String)> = actions
+        .iter()
+        .map(|(label, _)| (Button::new(), label.clone()))
+        .collect();
+    let close = Button::new();
+    close.set_attribute("aria-label", "Close").ok();
+    let controls = async {
+        // wait for the first frame, so that the transition runs
+        AnimationFrame::new().await;
+        AnimationFrame::new().await;
+        set_state("open");
+        let clicked = race((
+            // action buttons
+            race(
+                action_buttons
+                    .iter()
+                    .enumerate()
+                    .map(|(index, (button, _))| async move {
+                        button.until_click().await;
+                        Some(index)
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            async {
+                if dismissible {
+                    close.until_click().await;
+                } else {
+                    pending::<()>().await;
+                }
+                None
+            },
+            async {
+                match duration {
+                    Some(duration) => auto_dismiss(element, duration).await,
+                    None => pending().await,
+                }
+                None
+            },
+        ))
+        .await;
+        let result = clicked.and_then(|index| actions.into_iter().nth(index).map(|(_, v)| v));
+        {
+            let mut outcome = outcome.borrow_mut();
+            outcome.result = Some(result);
+            if let Some(waker) = outcome.waker.take() {
+                waker.wake();
+            }
+        }
+        set_state("leaving");
+        let mut transition_ends = element.until_event::<web_sys::Event>("transitionend".into());
+        race((
+            async {
+                (&mut transition_ends).await;
+            },
+            // in case there is no transition
+            TimeoutFuture::new(TRANSITION_MS + 50),
+        ))
+        .await;
+        queue.finished.borrow_mut().push(id);
+        queue.wake_host();
+        pending::<()>().await;
+    };
+    item.render(join((
+        content,
+        join(
+            action_buttons
+                .iter()
+                .map(|(button, label)| button.render(label.as_str().render()))
+                .collect::<Vec<_>>(),
+        ),
+        async {
+            if dismissible {
+                close.render("×".render()).await;
+            }
+        },
+        controls,
+    )))
+    .await;
+}
+
+/// Wait for the given duration, pausing while the pointer is over the element.
+async fn auto_dismiss(element: &HtmlElement, duration: Duration) {
+    let mut remaining = duration.as_millis() as f64;
+    let mut enters = element.until_event::<web_sys::Event>("pointerenter".into());
+    let mut leaves = element.until_event::<web_sys::Event>("pointerleave".into());
+    loop {
+        let started = js_sys::Date::now();
+        let hovered = race((
+            async {
+                TimeoutFuture::new(remaining as u32).await;
+                false
+            },
+            async {
+                (&mut enters).await;
+                true
+            },
+        ))
+        .await;
+        if !hovered {
+            return;
+        }
+        let elapsed = js_sys::Date::now() - started;
+        (&mut leaves).await;
+        remaining = remaining_after_hover(remaining, elapsed);
+    }
+}
+
+/// Time left on a timer that was paused by hovering.
+fn remaining_after_hover(remaining: f64, elapsed_before_hover: f64) -> f64 {
+    // give the user a moment to move back if they left by accident
+    (remaining - elapsed_before_hover).max(MIN_AFTER_HOVER_MS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hover_pause() {
+        assert_eq!(remaining_after_hover(5000.0, 1500.0), 3500.0);
+        assert_eq!(remaining_after_hover(5000.0, 4900.0), MIN_AFTER_HOVER_MS);
+    }
+}
@@ -0,0 +1,231 @@
+/*!
+Custom elements ([Web Components](https://developer.mozilla.org/en-US/docs/Web/API/Web_components))
+backed by async_ui futures.
+
+Use [define_custom_element] to register a tag. Every time an element with that
+tag is connected to the document, your function is called and the Future it
+returns is [mounted][crate::mount_at] inside the element.
+When the element is disconnected, the Future is dropped.
+
+This lets pages that don't use Rust (or even async_ui) embed parts of your app
+by writing plain HTML.
+
+```
+# use async_ui_web::{custom_element::define_custom_element, html::Button, join, prelude_traits::*};
+# use futures_lite::StreamExt;
+# let _ = || {
+define_custom_element("like-button", &["label"], |host| async move {
+    let button = Button::new();
+    join((
+        button.render(
+            host.attribute_stream("label")
+                .map(Option::unwrap_or_default)
+                .render_stream(),
+        ),
+        async {
+            loop {
+                button.until_click().await;
+                host.dispatch_event("liked", &wasm_bindgen::JsValue::NULL);
+            }
+        },
+    ))
+    .await;
+});
+# };
+```
+
+Then, in HTML: `<like-button label="Like"></like-button>`.
+*/
+
+use std::{cell::RefCell, collections::HashMap, future::Future, rc::Rc};
+
+use async_executor::Task;
+use futures_lite::{stream, Stream, StreamExt};
+use js_sys::{Array, Function};
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::{CustomEvent, CustomEventInit, HtmlElement};
+
+use crate::{mount_at, ReactiveCell};
+
+#[wasm_bindgen(inline_js = "
+let nextId = 0;
+export function define_async_ui_element(name, observed, connected, disconnected, attributeChanged) {
+    class AsyncUiElement extends HTMLElement {
+        static get observedAttributes() { return observed; }
+        constructor() {
+            super();
+            this.asyncUiId = nextId++;
+        }
+        connectedCallback() { connected(this.asyncUiId, this); }
+        disconnectedCallback() { disconnected(this.asyncUiId); }
+        attributeChangedCallback(name, oldValue, newValue) {
+            attributeChanged(this.asyncUiId, name, newValue);
+        }
+    }
+    customElements.define(name, AsyncUiElement);
+}
+")]
+extern "C" {
+    #[wasm_bindgen(catch)]
+    fn define_async_ui_element(
+        name: &str,
+        observed: Array,
+        connected: &Function,
+        disconnected: &Function,
+        attribute_changed: &Function,
+    ) -> Result<(), JsValue>;
+}
+
+/// The element your function is given by [define_custom_element].
+///
+/// This is cheap to clone.
+#[derive(Clone)]
+pub struct CustomElement {
+    inner: Rc<Inner>,
+}
+
+struct Inner {
+    element: HtmlElement,
+    attributes: ReactiveCell<HashMap<String, String>>,
+}
+
+impl CustomElement {
+    /// The custom element itself.
+    pub fn element(&self) -> &HtmlElement {
+        &self.inner.element
+    }
+    /// The current values of the observed attributes that are present.
+    ///
+    /// The cell is updated when an observed attribute changes,
+    /// so you can use its [until_change][ReactiveCell::until_change].
+    pub fn attributes(&self) -> &ReactiveCell<HashMap<String, String>> {
+        &self.inner.attributes
+    }
+    /// Get the current value of an observed attribute.
+    pub fn attribute(&self, name: &str) -> Option<String> {
+        self.inner.attributes.borrow().get(name).cloned()
+    }
+    /// A Stream yielding the current value of an observed attribute,
+    /// and then the new value every time it changes.
+    ///
+    /// `None` means the attribute is not present.
+    pub fn attribute_stream<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Stream<Item = Option<String>> + 'a {
+        let last = RefCell::new(None);
+        stream::once(())
+            .chain(self.inner.attributes.until_change())
+            .filter_map(move |_| {
+                let value = self.attribute(name);
+                let mut last = last.borrow_mut();
+                (last.as_ref() != Some(&value)).then(|| {
+                    *last = Some(value.clone());
+                    value
+                })
+            })
+    }
+    /// Dispatch a [CustomEvent] from the element.
+    ///
+    /// The event bubbles and crosses shadow DOM boundaries, so pages can
+    /// listen to it with `element.addEventListener(name, ...)`.
+    /// The `detail` is available as `event.detail`.
+    pub fn dispatch_event(&self, name: &str, detail: &JsValue) {
+        let init = CustomEventInit::new();
+        init.set_bubbles(true);
+        init.set_composed(true);
+        init.set_detail(detail);
+        let event = CustomEvent::new_with_event_init_dict(name, &init).unwrap_throw();
+        self.inner.element.dispatch_event(&event).unwrap_throw();
+    }
+}
+
+/// Register a custom element with the given tag name.
+///
+/// * `observed_attributes` lists the attributes whose changes are reported
+///   through [CustomElement::attributes].
+/// * `render` is called every time an element with this tag is connected to
+///   the document. The Future it returns is rendered inside the element
+///   until the element is disconnected.
+///
+/// Panics if the name is not a valid custom element name (it must contain a
+/// hyphen) or if it's already defined.
+///
+/// See the [module-level documentation][self] for an example.
+pub fn define_custom_element<F, Fut>(name: &str, observed_attributes: &[&str], render: F)
+where
+    F: Fn(CustomElement) -> Fut + 'static,
+    Fut: Future + 'static,
+{
+    struct Instance {
+        host: CustomElement,
+        _task: Task<()>,
+    }
+    let instances: Rc<RefCell<HashMap<u32, Instance>>> = Default::default();
+    let connected = Closure::<dyn Fn(u32, HtmlElement)>::new({
+        let instances = instances.clone();
+        let observed = observed_attributes
+            .iter()
+            .map(|&name| name.to_owned())
+            .collect::<Vec<_>>();
+        move |id, element: HtmlElement| {
+            let attributes = observed
+                .iter()
+                .filter_map(|name| Some((name.clone(), element.get_attribute(name)?)))
+                .collect();
+            let host = CustomElement {
+                inner: Rc::new(Inner {
+                    element: element.clone(),
+                    attributes: ReactiveCell::new(attributes),
+                }),
+            };
+            let fut = render(host.clone());
+            let task = mount_at(
+                async {
+                    fut.await;
+                },
+                element.into(),
+            );
+            instances
+                .borrow_mut()
+                .insert(id, Instance { host, _task: task });
+        }
+    });
+    let disconnected = Closure::<dyn Fn(u32)>::new({
+        let instances = instances.clone();
+        move |id| {
+            // bind the removed instance so that it's dropped after the borrow ends
+            let removed = instances.borrow_mut().remove(&id);
+            drop(removed);
+        }
+    });
+    let attribute_changed = Closure::<dyn Fn(u32, String, Option<String>)>::new({
+        move |id, name: String, value: Option<String>| {
+            let host = match instances.borrow().get(&id) {
+                Some(instance) => instance.host.clone(),
+                // not connected; the value will be read when it connects
+                None => return,
+            };
+            let mut attributes = host.inner.attributes.borrow_mut();
+            match value {
+                Some(value) => attributes.insert(name, value),
+                None => attributes.remove(&name),
+            };
+        }
+    });
+    define_async_ui_element(
+        name,
+        observed_attributes
+            .iter()
+            .map(|&name| JsValue::from_str(name))
+            .collect(),
+        connected.as_ref().unchecked_ref(),
+        disconnected.as_ref().unchecked_ref(),
+        attribute_changed.as_ref().unchecked_ref(),
+    )
+    .unwrap_throw();
+    // the element class lives for as long as the page does
+    connected.forget();
+    disconnected.forget();
+    attribute_changed.forget();
+}
@@ -0,0 +1,539 @@
+/*!
+Keyboard focus management.
+
+*   [FocusTrap] keeps <kbd>Tab</kbd> focus inside a dialog or menu, and gives
+    focus back to where it was when the trap goes away.
+*   [RovingTabindex] makes a toolbar or list a single <kbd>Tab</kbd> stop,
+    with the arrow keys moving between its items.
+*   The [EmitFocus] trait adds [until_focus_within][EmitFocus::until_focus_within]
+    to every element.
+
+```
+# use async_ui_web::{focus::FocusTrap, html::{Button, Input}, race, prelude_traits::*};
+# let _ = async {
+let trap = FocusTrap::new();
+let name = Input::new();
+let close = Button::new();
+trap.render(race((
+    name.render(),
+    close.render("Close".render()),
+    async {
+        close.until_click().await;
+    },
+)))
+.await;
+// the trap was dropped, so focus is back on the button that opened the dialog
+# };
+```
+*/
+
+use std::{
+    cell::{Cell, RefCell},
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use async_ui_web_core::window::DOCUMENT;
+use async_ui_web_html::{
+    events::{EmitElementEvent, EmitEvent, EventFutureStream},
+    nodes::Div,
+};
+use futures_lite::{Stream, StreamExt};
+use gloo_timers::future::TimeoutFuture;
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::{Element, FocusEvent, HtmlElement, KeyboardEvent, Node};
+
+use crate::{
+    observers::{MutationOptions, ObserveElement},
+    race,
+};
+
+/// Elements that may be reachable with <kbd>Tab</kbd>.
+/// [tabbable_elements] filters out the ones that aren't.
+const TABBABLE_SELECTOR: &str = "a[href], area[href], button, input, select, textarea, iframe, \
+    summary, audio[controls], video[controls], [contenteditable], [tabindex]";
+
+thread_local! {
+    /// Ids of the active focus traps, innermost last.
+    static TRAPS: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+    static NEXT_TRAP_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Get the elements inside `root` that can be reached with <kbd>Tab</kbd>,
+/// in document order.
+///
+/// This skips elements that are disabled, hidden, or have a negative `tabindex`.
+/// It does not look inside shadow roots.
+pub fn tabbable_elements(root: &Element) -> Vec<HtmlElement> {
+    let nodes = root.query_selector_all(TABBABLE_SELECTOR).unwrap_throw();
+    (0..nodes.length())
+        .filter_map(|i| nodes.get(i)?.dyn_into::<HtmlElement>().ok())
+        .filter(|element| {
+            element.tab_index() >= 0
+                && !element.matches(":disabled").unwrap_or(false)
+                && element.get_client_rects().length() > 0
+        })
+        .collect()
+}
+
+/// Listen to focus moving in and out of an element.
+///
+/// This is implemented for everything that is an [Element],
+/// and is included in the [prelude_traits][crate::prelude_traits].
+pub trait EmitFocus: AsRef<Element> {
+    /// Get a Stream that yields `true` when the keyboard focus moves into
+    /// this element (or anything inside it), and `false` when it moves out.
+    ///
+    /// Moving focus between elements inside this element yields nothing.
+    /// This matches the
+    /// [`:focus-within`](https://developer.mozilla.org/en-US/docs/Web/CSS/:focus-within)
+    /// CSS selector.
+    fn until_focus_within(&self) -> FocusWithinStream {
+        let element = self.as_ref();
+        let within = DOCUMENT
+            .with(|doc| doc.active_element())
+            .is_some_and(|active| element.contains(Some(&active)));
+        FocusWithinStream {
+            element: element.clone(),
+            within,
+            focusins: element.until_event("focusin".into()),
+            focusouts: element.until_event("focusout".into()),
+        }
+    }
+}
+
+impl<T: AsRef<Element>> EmitFocus for T {}
+
+/// Stream returned by [until_focus_within][EmitFocus::until_focus_within].
+pub struct FocusWithinStream {
+    element: Element,
+    within: bool,
+    focusins: EventFutureStream<FocusEvent>,
+    focusouts: EventFutureStream<FocusEvent>,
+}
+
+impl FocusWithinStream {
+    /// Whether the focus is inside the element right now.
+    pub fn is_within(&self) -> bool {
+        self.within
+    }
+}
+
+impl Stream for FocusWithinStream {
+    type Item = bool;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let before = this.within;
+        // `focusout` fires before the matching `focusin`
+        while let Poll::Ready(Some(ev)) = this.focusouts.poll_next(cx) {
+            // focus may be moving to another element inside
+            this.within = ev
+                .related_target()
+                .and_then(|target| target.dyn_into::<Node>().ok())
+                .is_some_and(|target| this.element.contains(Some(&target)));
+        }
+        while let Poll::Ready(Some(_)) = this.focusins.poll_next(cx) {
+            this.within = true;
+        }
+        if this.within != before {
+            Poll::Ready(Some(this.within))
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/**
+Keeps keyboard focus inside its content.
+
+While the trap is rendered, <kbd>Tab</kbd> and <kbd>Shift</kbd>+<kbd>Tab</kbd>
+cycle through the focusable elements in the content, and focus that escapes
+some other way (for example, by a script) is brought back.
+
+When the trap is first rendered, it focuses the first focusable element in
+the content. When it is dropped, it gives focus back to the element that had
+focus before, unless the focus has already moved somewhere else.
+
+If more than one trap is rendered at the same time, only the one rendered
+last is enforced. This lets a dialog open another dialog.
+
+See the [module-level documentation][self] for an example.
+*/
+pub struct FocusTrap {
+    container: Div,
+}
+
+impl Default for FocusTrap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FocusTrap {
+    /// Create a new focus trap.
+    pub fn new() -> Self {
+        let container = Div::new();
+        // so that the trap itself can hold focus if there is nothing else
+        container.set_tab_index(-1);
+        Self { container }
+    }
+    /// The `<div>` that the content is rendered in.
+    pub fn container(&self) -> &Div {
+        &self.container
+    }
+    /// Render the content inside the trap.
+    ///
+    /// This completes when `content` completes, with the same output.
+    pub async fn render<F: Future>(&self, content: F) -> F::Output {
+        let previous = DOCUMENT
+            .with(|doc| doc.active_element())
+            .and_then(|element| element.dyn_into::<HtmlElement>().ok());
+        let id = NEXT_TRAP_ID.with(|next| next.replace(next.get() + 1));
+        TRAPS.with(|traps| traps.borrow_mut().push(id));
+        let _guard = scopeguard::guard((), |_| {
+            TRAPS.with(|traps| traps.borrow_mut().retain(|&trap| trap != id));
+            let Some(previous) = previous else {
+                return;
+            };
+            let active = DOCUMENT.with(|doc| doc.active_element());
+            let body = DOCUMENT.with(|doc| doc.body());
+            let focus_was_ours = match &active {
+                None => true,
+                Some(active) => {
+                    body.as_ref().map(AsRef::as_ref) == Some(active)
+                        || self.container.contains(Some(active))
+                }
+            };
+            if focus_was_ours {
+                let _ = previous.focus();
+            }
+        });
+        race((self.container.render(content), self.enforce(id))).await
+    }
+    async fn enforce<T>(&self, id: u64) -> T {
+        let container: &HtmlElement = &self.container;
+        let mut keydowns = container.until_keydown();
+        let mut focusins = DOCUMENT.with(|doc| doc.until_event::<FocusEvent>("focusin".into()));
+        // wait for the content to be in the document
+        TimeoutFuture::new(0).await;
+        let mut last_inside = None;
+        if !self.contains_focus() {
+            self.focus_first();
+        }
+        loop {
+            let ev = race((async { Ok((&mut keydowns).await) }, async {
+                Err((&mut focusins).await)
+            }))
+            .await;
+            if TRAPS.with(|traps| traps.borrow().last() != Some(&id)) {
+                continue;
+            }
+            match ev {
+                Ok(ev) => self.handle_tab(&ev),
+                Err(ev) => {
+                    let target = ev.target().and_then(|t| t.dyn_into::<HtmlElement>().ok());
+                    match target {
+                        Some(target) if container.contains(Some(&target)) => {
+                            last_inside = Some(target)
+                        }
+                        _ => {
+                            // focus escaped; bring it back
+                            let refocused = last_inside
+                                .as_ref()
+                                .filter(|last| container.contains(Some(last)))
+                                .is_some_and(|last| last.focus().is_ok());
+                            if !refocused {
+                                self.focus_first();
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    fn handle_tab(&self, ev: &KeyboardEvent) {
+        if ev.key() != "Tab" || ev.ctrl_key() || ev.alt_key() || ev.meta_key() {
+            return;
+        }
+        let tabbable = tabbable_elements(&self.container);
+        let active = DOCUMENT.with(|doc| doc.active_element());
+        let current = active.and_then(|active| {
+            tabbable
+                .iter()
+                .position(|element| AsRef::<Element>::as_ref(element) == &active)
+        });
+        if tabbable.is_empty() {
+            // nowhere to go; stay on the trap itself
+            ev.prevent_default();
+        } else if let Some(next) = wrap_tab(current, tabbable.len(), ev.shift_key()) {
+            ev.prevent_default();
+            let _ = tabbable[next].focus();
+        }
+    }
+    fn contains_focus(&self) -> bool {
+        DOCUMENT
+            .with(|doc| doc.active_element())
+            .is_some_and(|active| self.container.contains(Some(&active)))
+    }
+    /// Focus the first focusable element, or the trap itself.
+    fn focus_first(&self) {
+        let _ = match tabbable_elements(&self.container).first() {
+            Some(element) => element.focus(),
+            None => self.container.focus(),
+        };
+    }
+}
+
+/// Where <kbd>Tab</kbd> should go in a trap with `len` tabbable elements,
+/// if the browser's own behavior would leave the trap.
+fn wrap_tab(current: Option<usize>, len: usize, backwards: bool) -> Option<usize> {
+    match current {
+        _ if len == 0 => None,
+        None if backwards => Some(len - 1),
+        None => Some(0),
+        Some(0) if backwards => Some(len - 1),
+        Some(index) if !backwards && index + 1 == len => Some(0),
+        Some(_) => None,
+    }
+}
+
+/// Which arrow keys a [RovingTabindex] responds to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Orientation {
+    /// Left and Right.
+    Horizontal,
+    /// Up and Down.
+    Vertical,
+    /// All four arrow keys.
+    Both,
+}
+
+/**
+Makes a group of items a single <kbd>Tab</kbd> stop, moving between items
+with the arrow keys.
+
+This is the
+[roving tabindex](https://www.w3.org/WAI/ARIA/apg/practices/keyboard-interface/#kbd_roving_tabindex)
+pattern used by toolbars, menus, listboxes, and grids: one item has
+`tabindex="0"` and every other item has `tabindex="-1"`.
+<kbd>Home</kbd> and <kbd>End</kbd> move to the first and last item.
+
+By default, the items are the child elements of the container. Items may be
+added, removed, and moved while the helper runs (for example, by a
+[DynamicList][crate::lists::DynamicList]).
+
+```
+# use async_ui_web::{focus::{Orientation, RovingTabindex}, html::{Button, Div}, join, lists::DynamicList, prelude_traits::*};
+# let _ = async {
+let toolbar = Div::new();
+toolbar.set_attribute("role", "toolbar").unwrap();
+let list = DynamicList::new();
+for (key, label) in ["Bold", "Italic", "Underline"].into_iter().enumerate() {
+    list.insert(key, Box::pin(async move {
+        Button::new().render(label.render()).await;
+    }), None);
+}
+let roving = RovingTabindex::new(&toolbar, Orientation::Horizontal);
+join((toolbar.render(list.render()), roving.run())).await;
+# };
+```
+*/
+pub struct RovingTabindex {
+    container: HtmlElement,
+    orientation: Orientation,
+    wrap: Cell<bool>,
+    item_selector: RefCell<Option<String>>,
+    active: RefCell<Option<HtmlElement>>,
+}
+
+impl RovingTabindex {
+    /// Manage the items in the given container.
+    pub fn new(container: &impl AsRef<HtmlElement>, orientation: Orientation) -> Self {
+        Self {
+            container: container.as_ref().clone(),
+            orientation,
+            wrap: Cell::new(true),
+            item_selector: RefCell::new(None),
+            active: RefCell::new(None),
+        }
+    }
+    /// Whether moving past the last item goes back to the first one
+    /// (and vice versa). The default is true.
+    pub fn set_wrap(&self, wrap: bool) {
+        self.wrap.set(wrap);
+    }
+    /// Use the descendants of the container that match the given CSS selector
+    /// as the items, instead of its children.
+    ///
+    /// Call this before [run][Self::run].
+    pub fn set_item_selector(&self, selector: &str) {
+        *self.item_selector.borrow_mut() = Some(selector.to_owned());
+    }
+    /// Move focus to the item at the given position, if there is one.
+    pub fn focus_item(&self, index: usize) {
+        let items = self.items();
+        if let Some(item) = items.get(index) {
+            self.set_active(&items, index);
+            let _ = item.focus();
+        }
+    }
+    /// Keep the `tabindex` of the items up to date and handle the arrow keys.
+    ///
+    /// This async method never completes.
+    pub async fn run(&self) {
+        enum Wake {
+            Mutation,
+            Key(KeyboardEvent),
+            Focus(Option<Node>),
+        }
+        let mut mutations = self.container.until_mutation(&MutationOptions {
+            child_list: true,
+            subtree: self.item_selector.borrow().is_some(),
+            ..Default::default()
+        });
+        let mut keydowns = self.container.until_keydown();
+        let mut focusins = self.container.until_focusin();
+        self.sync();
+        loop {
+            let wake = race((
+                async {
+                    mutations.next().await;
+                    Wake::Mutation
+                },
+                async { Wake::Key((&mut keydowns).await) },
+                async {
+                    let ev = (&mut focusins).await;
+                    Wake::Focus(ev.target().and_then(|t| t.dyn_into().ok()))
+                },
+            ))
+            .await;
+            match wake {
+                Wake::Mutation => self.sync(),
+                Wake::Key(ev) => {
+                    if ev.ctrl_key() || ev.alt_key() || ev.meta_key() {
+                        continue;
+                    }
+                    let items = self.items();
+                    let target = ev.target().and_then(|t| t.dyn_into::<Node>().ok());
+                    let Some(current) = position_of(&items, target.as_ref()) else {
+                        continue;
+                    };
+                    let next = roving_next(
+                        current,
+                        items.len(),
+                        &ev.key(),
+                        self.orientation,
+                        self.wrap.get(),
+                    );
+                    if let Some(next) = next {
+                        ev.prevent_default();
+                        self.set_active(&items, next);
+                        let _ = items[next].focus();
+                    }
+                }
+                Wake::Focus(target) => {
+                    // the user may have clicked an item
+                    let items = self.items();
+                    if let Some(index) = position_of(&items, target.as_ref()) {
+                        self.set_active(&items, index);
+                    }
+                }
+            }
+        }
+    }
+    fn items(&self) -> Vec<HtmlElement> {
+        match &*self.item_selector.borrow() {
+            Some(selector) => {
+                let nodes = self.container.query_selector_all(selector).unwrap_throw();
+                (0..nodes.length())
+                    .filter_map(|i| nodes.get(i)?.dyn_into().ok())
+                    .collect()
+            }
+            None => {
+                let children = self.container.children();
+                (0..children.length())
+                    .filter_map(|i| children.item(i)?.dyn_into().ok())
+                    .collect()
+            }
+        }
+    }
+    /// Make sure exactly one item is tabbable, after items were added or removed.
+    fn sync(&self) {
+        let items = self.items();
+        let active = self.active.borrow().clone();
+        let index = active
+            .and_then(|active| items.iter().position(|item| item == &active))
+            .unwrap_or(0);
+        if !items.is_empty() {
+            self.set_active(&items, index);
+        }
+    }
+    fn set_active(&self, items: &[HtmlElement], index: usize) {
+        for (i, item) in items.iter().enumerate() {
+            item.set_tab_index(if i == index { 0 } else { -1 });
+        }
+        *self.active.borrow_mut() = items.get(index).cloned();
+    }
+}
+
+/// The position of the item that is or contains `target`.
+fn position_of(items: &[HtmlElement], target: Option<&Node>) -> Option<usize> {
+    let target = target?;
+    items.iter().position(|item| item.contains(Some(target)))
+}
+
+/// Which item a key press in a roving tabindex group should move to.
+pub(crate) fn roving_next(
+    current: usize,
+    len: usize,
+    key: &str,
+    orientation: Orientation,
+    wrap: bool,
+) -> Option<usize> {
+    use Orientation::*;
+    let forward = match (key, orientation) {
+        ("Home", _) => return (current != 0).then_some(0),
+        ("End", _) => return (current + 1 < len).then(|| len - 1),
+        ("ArrowRight", Horizontal | Both) | ("ArrowDown", Vertical | Both) => true,
+        ("ArrowLeft", Horizontal | Both) | ("ArrowUp", Vertical | Both) => false,
+        _ => return None,
+    };
+    match (forward, current) {
+        (true, _) if current + 1 < len => Some(current + 1),
+        (true, _) => (wrap && len > 1).then_some(0),
+        (false, 0) => (wrap && len > 1).then(|| len - 1),
+        (false, _) => Some(current - 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tab_wraps_at_edges() {
+        assert_eq!(wrap_tab(Some(2), 3, false), Some(0));
+        assert_eq!(wrap_tab(Some(0), 3, true), Some(2));
+        assert_eq!(wrap_tab(Some(1), 3, false), None);
+        assert_eq!(wrap_tab(Some(1), 3, true), None);
+        assert_eq!(wrap_tab(None, 3, false), Some(0));
+        assert_eq!(wrap_tab(None, 3, true), Some(2));
+        assert_eq!(wrap_tab(None, 0, false), None);
+    }
+
+    #[test]
+    fn roving_keys() {
+        use Orientation::*;
+        assert_eq!(roving_next(0, 3, "ArrowRight", Horizontal, false), Some(1));
+        assert_eq!(roving_next(0, 3, "ArrowDown", Horizontal, false), None);
+        assert_eq!(roving_next(2, 3, "ArrowDown", Vertical, false), None);
+        assert_eq!(roving_next(2, 3, "ArrowDown", Vertical, true), Some(0));
+        assert_eq!(roving_next(0, 3, "ArrowUp", Both, true), Some(2));
+        assert_eq!(roving_next(1, 3, "ArrowLeft", Both, false), Some(0));
+        assert_eq!(roving_next(1, 3, "Home", Vertical, false), Some(0));
+        assert_eq!(roving_next(1, 3, "End", Vertical, false), Some(2));
+        assert_eq!(roving_next(2, 3, "End", Vertical, false), None);
+    }
+}
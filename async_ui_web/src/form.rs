@@ -0,0 +1,535 @@
+/*!
+Form validation.
+
+A [Form] keeps track of a set of [Field]s.
+Each field parses the content of an input element, checks it with
+validators (sync or async), and tracks whether it is dirty, touched, and valid.
+The validity is reported to the browser through
+[setCustomValidity](https://developer.mozilla.org/en-US/docs/Web/API/HTMLObjectElement/setCustomValidity),
+so `:invalid` CSS selectors and the browser's built-in messages work as usual.
+
+```
+# use async_ui_web::{form::Form, html, join};
+# let _ = async {
+let form_elem = html::Form::new();
+let (name_input, age_input) = (html::Input::new_text(), html::Input::new_number());
+let form = Form::new(&form_elem);
+let name = form
+    .text_field()
+    .validate(|name| match name.trim().is_empty() {
+        true => Err("Please enter a name.".into()),
+        false => Ok(()),
+    })
+    .validate_async(|name| {
+        let name = name.clone();
+        async move {
+            // ask the server if the name is taken...
+            # let _ = name;
+            Ok(())
+        }
+    });
+let age = form.field(|s| s.parse::<u8>().map_err(|_| "Please enter your age.".into()));
+join((
+    form_elem.render(join((
+        name_input.render(),
+        name.attach(&name_input),
+        name.render_message(),
+        age_input.render(),
+        age.attach(&age_input),
+        age.render_message(),
+    ))),
+    async {
+        let submission = form.until_valid_submit().await;
+        let (name, age) = (submission.get(&name), submission.get(&age));
+        // ...
+        # let _ = (name, age);
+    },
+))
+.await;
+# };
+```
+*/
+
+use std::{
+    cell::RefCell,
+    future::{pending, poll_fn, Future},
+    rc::Rc,
+    task::Poll,
+};
+
+use async_ui_web_html::nodes::Text;
+use futures_lite::{future::BoxedLocal, StreamExt};
+use wasm_bindgen::UnwrapThrowExt;
+use web_sys::HtmlFormElement;
+
+use crate::{
+    bind::ValueElement,
+    event_traits::{EmitElementEvent, EmitEvent, EmitHtmlElementEvent},
+    join, race, ReactiveCell,
+};
+
+/// The validation state of a [Field].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FieldStatus {
+    /// The field hasn't been checked yet, or an async validator is running.
+    Validating,
+    /// The content parsed and passed all validators.
+    Valid,
+    /// The content failed to parse or failed a validator.
+    Invalid(String),
+}
+
+impl FieldStatus {
+    /// Whether the status is [FieldStatus::Valid].
+    pub fn is_valid(&self) -> bool {
+        matches!(self, Self::Valid)
+    }
+    /// The error message, if the status is [FieldStatus::Invalid].
+    pub fn message(&self) -> Option<&str> {
+        match self {
+            Self::Invalid(message) => Some(message),
+            _ => None,
+        }
+    }
+}
+
+/// A set of [Field]s attached to an HTML `<form>` element.
+///
+/// See the [module-level documentation][self].
+pub struct Form {
+    element: HtmlFormElement,
+    fields: RefCell<Vec<Rc<dyn AnyField>>>,
+}
+
+/// The non-generic part of a field, used by the [Form] it belongs to.
+trait AnyField {
+    fn touch(&self);
+    fn status(&self) -> &ReactiveCell<FieldStatus>;
+    /// How many [attach][Field::attach] Futures of the field are running.
+    fn attached(&self) -> &ReactiveCell<usize>;
+}
+
+/// What a submit finds when checking the fields.
+#[derive(Debug, PartialEq, Eq)]
+enum SubmitCheck {
+    /// Some attached field is still being validated.
+    Pending,
+    /// Every attached field is valid.
+    Valid,
+    /// Some attached field is invalid.
+    Invalid,
+}
+
+/// Check the attached fields. Fields that aren't attached are not validated,
+/// so they are left out.
+fn submit_check(fields: &[Rc<dyn AnyField>]) -> SubmitCheck {
+    let mut check = SubmitCheck::Valid;
+    for field in fields.iter().filter(|field| *field.attached().borrow() > 0) {
+        match &*field.status().borrow() {
+            FieldStatus::Validating => return SubmitCheck::Pending,
+            FieldStatus::Invalid(_) => check = SubmitCheck::Invalid,
+            FieldStatus::Valid => {}
+        }
+    }
+    check
+}
+
+/// Wait for the pending validators of the attached fields, then check them.
+async fn settle(fields: &[Rc<dyn AnyField>]) -> SubmitCheck {
+    loop {
+        let check = submit_check(fields);
+        if check != SubmitCheck::Pending {
+            return check;
+        }
+        // a field may finish validating, or be detached
+        let mut statuses: Vec<_> = fields
+            .iter()
+            .map(|field| field.status().until_change())
+            .collect();
+        let mut attachments: Vec<_> = fields
+            .iter()
+            .map(|field| field.attached().until_change())
+            .collect();
+        poll_fn(|cx| {
+            // poll all of them, so that they all register for wakeups
+            let mut changed = false;
+            for changes in statuses.iter_mut() {
+                changed |= changes.poll_next(cx).is_ready();
+            }
+            for changes in attachments.iter_mut() {
+                changed |= changes.poll_next(cx).is_ready();
+            }
+            if changed {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
+    }
+}
+
+impl Form {
+    /// Create a form for the given `<form>` element.
+    pub fn new(element: &impl AsRef<HtmlFormElement>) -> Self {
+        Self {
+            element: element.as_ref().clone(),
+            fields: RefCell::new(Vec::new()),
+        }
+    }
+    /// Register a field whose content is converted by the given parser.
+    ///
+    /// If the parser returns an error, the error string becomes the
+    /// validation message of the field.
+    pub fn field<T: 'static>(
+        &self,
+        parser: impl Fn(&str) -> Result<T, String> + 'static,
+    ) -> Field<T> {
+        let inner = Rc::new(FieldInner {
+            parser: Box::new(parser),
+            validators: RefCell::new(Vec::new()),
+            value: ReactiveCell::new(None),
+            status: ReactiveCell::new(FieldStatus::Validating),
+            dirty: ReactiveCell::new(false),
+            touched: ReactiveCell::new(false),
+            attached: ReactiveCell::new(0),
+        });
+        self.fields.borrow_mut().push(inner.clone());
+        Field { inner }
+    }
+    /// Register a field that takes the content as-is.
+    pub fn text_field(&self) -> Field<String> {
+        self.field(|s| Ok(s.to_owned()))
+    }
+    /// Whether every [attached][Field::attach] field of the form is valid.
+    pub fn is_valid(&self) -> bool {
+        submit_check(&self.fields.borrow()) == SubmitCheck::Valid
+    }
+    /// Wait until the user submits the form with all fields valid.
+    ///
+    /// The browser's default submit action (navigating away) is prevented.
+    ///
+    /// On each submit, every field is marked touched and pending async
+    /// validators are waited for. If some fields are invalid, the browser is
+    /// asked to [report them](https://developer.mozilla.org/en-US/docs/Web/API/HTMLFormElement/reportValidity)
+    /// and this keeps waiting for the next submit.
+    ///
+    /// Fields are only validated while their [attach][Field::attach] Future
+    /// is running. Fields that aren't attached when the form is submitted
+    /// (such as inputs that aren't shown) are left out of the check.
+    pub async fn until_valid_submit(&self) -> ValidSubmission {
+        let mut submits = self.element.until_event::<web_sys::Event>("submit".into());
+        submits.set_prevent_default(true);
+        loop {
+            submits.next().await;
+            let fields = self.fields.borrow().clone();
+            fields.iter().for_each(|field| field.touch());
+            if settle(&fields).await == SubmitCheck::Valid {
+                return ValidSubmission { _private: () };
+            }
+            self.element.report_validity();
+        }
+    }
+}
+
+/// Proof that a [Form] was submitted with all fields valid.
+/// Use it to get the parsed values of the fields.
+pub struct ValidSubmission {
+    _private: (),
+}
+
+impl ValidSubmission {
+    /// Get the parsed value of a field of the submitted form.
+    ///
+    /// Panics if the field has no valid value. This can only happen if it
+    /// belongs to another form, or it wasn't attached when the form was
+    /// submitted; use [try_get][Self::try_get] for fields that may not be.
+    pub fn get<T: Clone>(&self, field: &Field<T>) -> T {
+        self.try_get(field).expect_throw("field is not valid")
+    }
+    /// Get the parsed value of a field of the submitted form,
+    /// or `None` if the field isn't attached or isn't valid.
+    pub fn try_get<T: Clone>(&self, field: &Field<T>) -> Option<T> {
+        let inner = &*field.inner;
+        if *inner.attached.borrow() == 0 || !inner.status.borrow().is_valid() {
+            return None;
+        }
+        inner.value.borrow().clone()
+    }
+}
+
+/// A form field, created by [Form::field] or [Form::text_field].
+///
+/// The field does nothing until you [attach][Self::attach] it to an element.
+pub struct Field<T> {
+    inner: Rc<FieldInner<T>>,
+}
+
+impl<T> Clone for Field<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+type Parser<T> = Box<dyn Fn(&str) -> Result<T, String>>;
+type ValidationResult = Result<(), String>;
+type AsyncValidator<T> = Box<dyn Fn(&T) -> BoxedLocal<ValidationResult>>;
+
+enum Validator<T> {
+    Sync(Box<dyn Fn(&T) -> ValidationResult>),
+    Async(AsyncValidator<T>),
+}
+
+struct FieldInner<T> {
+    parser: Parser<T>,
+    validators: RefCell<Vec<Validator<T>>>,
+    value: ReactiveCell<Option<T>>,
+    status: ReactiveCell<FieldStatus>,
+    dirty: ReactiveCell<bool>,
+    touched: ReactiveCell<bool>,
+    attached: ReactiveCell<usize>,
+}
+
+impl<T> AnyField for FieldInner<T> {
+    fn touch(&self) {
+        set_if_changed(&self.touched, true);
+    }
+    fn status(&self) -> &ReactiveCell<FieldStatus> {
+        &self.status
+    }
+    fn attached(&self) -> &ReactiveCell<usize> {
+        &self.attached
+    }
+}
+
+/// Write to the cell only if the value is different, to avoid waking listeners.
+fn set_if_changed<T: PartialEq>(cell: &ReactiveCell<T>, value: T) {
+    if *cell.borrow() != value {
+        *cell.borrow_mut() = value;
+    }
+}
+
+impl<T: 'static> Field<T> {
+    /// Add a validator. Validators run in the order they are added,
+    /// and the first error becomes the validation message.
+    ///
+    /// Sync validators always run before async ones.
+    pub fn validate(self, validator: impl Fn(&T) -> Result<(), String> + 'static) -> Self {
+        self.inner
+            .validators
+            .borrow_mut()
+            .push(Validator::Sync(Box::new(validator)));
+        self
+    }
+    /// Add an async validator, for checks that need to talk to a server.
+    ///
+    /// While it runs, the field status is [FieldStatus::Validating].
+    /// If the user edits the field before it finishes, it is cancelled.
+    pub fn validate_async<F>(self, validator: impl Fn(&T) -> F + 'static) -> Self
+    where
+        F: Future<Output = Result<(), String>> + 'static,
+    {
+        self.inner
+            .validators
+            .borrow_mut()
+            .push(Validator::Async(Box::new(move |value| {
+                Box::pin(validator(value))
+            })));
+        self
+    }
+    /// The parsed value. `None` if the content failed to parse.
+    ///
+    /// The value may be set even if a validator rejects it.
+    pub fn value(&self) -> &ReactiveCell<Option<T>> {
+        &self.inner.value
+    }
+    /// The validation state.
+    pub fn status(&self) -> &ReactiveCell<FieldStatus> {
+        &self.inner.status
+    }
+    /// Whether the user has edited the field.
+    pub fn dirty(&self) -> &ReactiveCell<bool> {
+        &self.inner.dirty
+    }
+    /// Whether the field has lost focus since it was attached,
+    /// or the form has been submitted.
+    pub fn touched(&self) -> &ReactiveCell<bool> {
+        &self.inner.touched
+    }
+    /// Validate the content of the given element as the user edits it.
+    ///
+    /// This also sets the [custom validity](ValueElement::set_element_validity)
+    /// of the element. The returned Future never completes.
+    pub async fn attach(&self, element: &impl ValueElement) {
+        let inner = &*self.inner;
+        *inner.attached.borrow_mut() += 1;
+        let _guard = scopeguard::guard((), |_| *inner.attached.borrow_mut() -= 1);
+        let mut inputs = element.as_ref().until_input();
+        let mut blurs = element.as_ref().until_blur();
+        race((
+            async {
+                loop {
+                    race((
+                        async {
+                            self.check(element).await;
+                            pending::<()>().await
+                        },
+                        async {
+                            inputs.next().await;
+                        },
+                    ))
+                    .await;
+                    set_if_changed(&inner.dirty, true);
+                }
+            },
+            async {
+                while blurs.next().await.is_some() {
+                    inner.touch();
+                }
+            },
+        ))
+        .await
+    }
+    /// Render the validation message of the field as a text node.
+    ///
+    /// The message is only shown once the field is [touched][Self::touched].
+    /// The returned Future never completes.
+    pub async fn render_message(&self) {
+        let inner = &*self.inner;
+        let text = Text::new();
+        let mut changes = inner.status.until_change().or(inner.touched.until_change());
+        join((text.render(), async {
+            loop {
+                {
+                    let status = inner.status.borrow();
+                    let message = status.message().filter(|_| *inner.touched.borrow());
+                    text.set_data(message.unwrap_or_default());
+                }
+                changes.next().await;
+            }
+        }))
+        .await;
+    }
+    async fn check(&self, element: &impl ValueElement) {
+        let inner = &*self.inner;
+        let set_status = |status: FieldStatus| {
+            element.set_element_validity(status.message().unwrap_or_default());
+            set_if_changed(&inner.status, status);
+        };
+        let value = match (inner.parser)(&element.element_value()) {
+            Ok(value) => value,
+            Err(message) => {
+                *inner.value.borrow_mut() = None;
+                set_status(FieldStatus::Invalid(message));
+                return;
+            }
+        };
+        let mut pending_checks = Vec::new();
+        let mut result = Ok(());
+        for validator in inner.validators.borrow().iter() {
+            match validator {
+                Validator::Sync(validator) => result = result.and_then(|_| validator(&value)),
+                Validator::Async(validator) => pending_checks.push(validator(&value)),
+            }
+        }
+        *inner.value.borrow_mut() = Some(value);
+        if result.is_ok() && !pending_checks.is_empty() {
+            set_status(FieldStatus::Validating);
+            for check in pending_checks {
+                result = check.await;
+                if result.is_err() {
+                    break;
+                }
+            }
+        }
+        set_status(match result {
+            Ok(()) => FieldStatus::Valid,
+            Err(message) => FieldStatus::Invalid(message),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestField {
+        status: ReactiveCell<FieldStatus>,
+        attached: ReactiveCell<usize>,
+    }
+
+    impl AnyField for TestField {
+        fn touch(&self) {}
+        fn status(&self) -> &ReactiveCell<FieldStatus> {
+            &self.status
+        }
+        fn attached(&self) -> &ReactiveCell<usize> {
+            &self.attached
+        }
+    }
+
+    fn field(status: FieldStatus, attached: bool) -> Rc<TestField> {
+        Rc::new(TestField {
+            status: ReactiveCell::new(status),
+            attached: ReactiveCell::new(attached as usize),
+        })
+    }
+
+    fn fields(list: &[Rc<TestField>]) -> Vec<Rc<dyn AnyField>> {
+        list.iter()
+            .map(|field| field.clone() as Rc<dyn AnyField>)
+            .collect()
+    }
+
+    #[test]
+    fn checks() {
+        use FieldStatus::*;
+        let check = |list: &[Rc<TestField>]| submit_check(&fields(list));
+        assert_eq!(check(&[]), SubmitCheck::Valid);
+        assert_eq!(
+            check(&[field(Valid, true), field(Valid, true)]),
+            SubmitCheck::Valid
+        );
+        assert_eq!(
+            check(&[field(Valid, true), field(Invalid("no".into()), true)]),
+            SubmitCheck::Invalid
+        );
+        assert_eq!(
+            check(&[field(Invalid("no".into()), true), field(Validating, true)]),
+            SubmitCheck::Pending
+        );
+        // never attached, so never validated
+        assert_eq!(
+            check(&[field(Valid, true), field(Validating, false)]),
+            SubmitCheck::Valid
+        );
+        assert_eq!(
+            check(&[field(Invalid("stale".into()), false)]),
+            SubmitCheck::Valid
+        );
+    }
+
+    #[test]
+    fn settles() {
+        let pending = field(FieldStatus::Validating, true);
+        let list = fields(&[field(FieldStatus::Valid, true), pending.clone()]);
+        // an async validator finishes
+        let (check, ()) =
+            futures_lite::future::block_on(futures_lite::future::zip(settle(&list), async {
+                futures_lite::future::yield_now().await;
+                *pending.status.borrow_mut() = FieldStatus::Invalid("taken".into());
+            }));
+        assert_eq!(check, SubmitCheck::Invalid);
+
+        // the field is detached while validating
+        *pending.status.borrow_mut() = FieldStatus::Validating;
+        let (check, ()) =
+            futures_lite::future::block_on(futures_lite::future::zip(settle(&list), async {
+                futures_lite::future::yield_now().await;
+                *pending.attached.borrow_mut() = 0;
+            }));
+        assert_eq!(check, SubmitCheck::Valid);
+    }
+}
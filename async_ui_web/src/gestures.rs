@@ -0,0 +1,676 @@
+/*!
+Pointer gesture recognizers.
+
+The [EmitGesture] trait adds methods to every element for listening to
+common gestures. Each returns a [Stream] that turns raw pointer events
+(from the mouse, touch, or a pen) into typed gesture items.
+
+```
+# use async_ui_web::{html::Div, prelude_traits::*, gestures::GesturePhase, style::{px, Transform}};
+# use futures_lite::StreamExt;
+# let _ = async {
+let card = Div::new();
+// touch drags should move the card rather than scroll the page
+card.set_style("touch-action", "none");
+let mut drags = card.until_drag_gesture();
+while let Some(drag) = drags.next().await {
+    match drag.phase {
+        GesturePhase::Start | GesturePhase::Move => {
+            card.set_style("transform", Transform::new().translate(px(drag.dx), px(drag.dy)))
+        }
+        GesturePhase::End | GesturePhase::Cancel => card.del_style("transform"),
+    }
+}
+# };
+```
+
+Gestures that follow the pointer outside the element (drag, pinch, and swipe)
+use [pointer capture](https://developer.mozilla.org/en-US/docs/Web/API/Element/setPointerCapture).
+All event listeners are removed, and any pointer capture is released,
+when the stream is dropped.
+
+On touch screens, the browser takes over touches for scrolling and zooming
+unless the element has an appropriate
+[`touch-action`](https://developer.mozilla.org/en-US/docs/Web/CSS/touch-action)
+style (such as `none`).
+*/
+
+use std::{
+    f64::consts::PI,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use async_ui_web_html::events::{EmitEvent, EventBuffer, EventFutureStream};
+use futures_lite::Stream;
+use gloo_timers::future::TimeoutFuture;
+use web_sys::{Element, PointerEvent};
+
+/// How far (in CSS pixels) a pointer has to move before it counts as moving.
+/// Shorter movements are left alone, so clicks still work.
+const DEFAULT_THRESHOLD: f64 = 4.0;
+
+/// Listen to pointer gestures.
+///
+/// This is implemented for everything that is an [Element],
+/// and is included in the [prelude_traits][crate::prelude_traits].
+///
+/// See the [module-level documentation][self] for more.
+pub trait EmitGesture: AsRef<Element> {
+    /// Get a Stream of drags (or pans) made with the primary pointer.
+    ///
+    /// A drag starts once the pointer is pressed on the element and moved
+    /// past a small threshold. The pointer is captured until it is released.
+    ///
+    /// This is named `until_drag_gesture` because
+    /// [until_drag][crate::event_traits::EmitHtmlElementEvent::until_drag]
+    /// is the HTML `drag` event.
+    fn until_drag_gesture(&self) -> DragGestureStream {
+        DragGestureStream {
+            input: PointerInput::new(self.as_ref()),
+            threshold: DEFAULT_THRESHOLD,
+            state: DragState::Idle,
+        }
+    }
+    /// Get a Stream of two-pointer pinches (and rotations).
+    fn until_pinch(&self) -> PinchStream {
+        PinchStream {
+            input: PointerInput::new(self.as_ref()),
+            pointers: Vec::new(),
+            pinch: None,
+        }
+    }
+    /// Get a Stream that fires when the primary pointer is held down on the
+    /// element, without moving, for some time (500 ms by default).
+    fn until_long_press(&self) -> LongPressStream {
+        LongPressStream {
+            input: PointerInput::new(self.as_ref()),
+            threshold: DEFAULT_THRESHOLD,
+            duration: Duration::from_millis(500),
+            pressed: None,
+        }
+    }
+    /// Get a Stream of quick flicks made with the primary pointer.
+    fn until_swipe(&self) -> SwipeStream {
+        SwipeStream {
+            input: PointerInput::new(self.as_ref()),
+            threshold: DEFAULT_THRESHOLD,
+            min_distance: 30.0,
+            min_velocity: 0.3,
+            pressed: None,
+        }
+    }
+}
+
+impl<T: AsRef<Element>> EmitGesture for T {}
+
+/// The stage of a continuous gesture.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GesturePhase {
+    /// The gesture has just been recognized.
+    Start,
+    /// The gesture is ongoing and the pointers moved.
+    Move,
+    /// The pointers were released, completing the gesture.
+    End,
+    /// The browser interrupted the gesture (a `pointercancel` event).
+    Cancel,
+}
+
+/// An item of [DragGestureStream].
+///
+/// Positions are in client (viewport) coordinates, in CSS pixels.
+#[derive(Clone, Debug)]
+pub struct DragGesture {
+    pub phase: GesturePhase,
+    /// Current position of the pointer.
+    pub x: f64,
+    pub y: f64,
+    /// Movement since the pointer was pressed.
+    pub dx: f64,
+    pub dy: f64,
+    /// Movement since the previous item.
+    pub step_x: f64,
+    pub step_y: f64,
+    /// The event that produced this item.
+    pub event: PointerEvent,
+}
+
+/// An item of [PinchStream].
+#[derive(Clone, Debug)]
+pub struct PinchGesture {
+    pub phase: GesturePhase,
+    /// Distance between the two pointers, relative to when the pinch started.
+    /// Greater than 1 when zooming in.
+    pub scale: f64,
+    /// Clockwise rotation of the line between the two pointers since the pinch
+    /// started, in radians, between -π and π.
+    pub rotation: f64,
+    /// Midpoint of the two pointers, in client coordinates.
+    pub center_x: f64,
+    pub center_y: f64,
+}
+
+/// An item of [LongPressStream].
+#[derive(Clone, Debug)]
+pub struct LongPress {
+    /// Where the pointer was pressed, in client coordinates.
+    pub x: f64,
+    pub y: f64,
+    /// The `pointerdown` event that started the press.
+    pub event: PointerEvent,
+}
+
+/// The direction of a [Swipe].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// An item of [SwipeStream].
+#[derive(Clone, Debug)]
+pub struct Swipe {
+    pub direction: SwipeDirection,
+    /// Movement from where the pointer was pressed to where it was released.
+    pub dx: f64,
+    pub dy: f64,
+    /// Speed along the swipe direction, in CSS pixels per millisecond.
+    pub velocity: f64,
+    /// The `pointerup` event that ended the swipe.
+    pub event: PointerEvent,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Down,
+    Move,
+    Up,
+    Cancel,
+    Leave,
+}
+
+const KINDS: [(Kind, &str); 5] = [
+    (Kind::Down, "pointerdown"),
+    (Kind::Move, "pointermove"),
+    (Kind::Up, "pointerup"),
+    (Kind::Cancel, "pointercancel"),
+    (Kind::Leave, "pointerleave"),
+];
+
+/// All the pointer events of an element, merged into one stream in the order
+/// they happened.
+struct PointerInput {
+    element: Element,
+    sources: [EventFutureStream<PointerEvent>; 5],
+    heads: [Option<PointerEvent>; 5],
+}
+
+impl PointerInput {
+    fn new(element: &Element) -> Self {
+        Self {
+            element: element.clone(),
+            sources: KINDS.map(|(kind, name)| {
+                let mut source = element.until_event(name.into());
+                if kind != Kind::Move {
+                    source.set_buffer(EventBuffer::Unbounded);
+                }
+                source
+            }),
+            heads: Default::default(),
+        }
+    }
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<(Kind, PointerEvent)> {
+        for (source, head) in self.sources.iter_mut().zip(self.heads.iter_mut()) {
+            if head.is_none() {
+                if let Poll::Ready(Some(ev)) = Pin::new(source).poll_next(cx) {
+                    *head = Some(ev);
+                }
+            }
+        }
+        // events are queued by separate listeners; take the earliest one
+        let earliest = (0..KINDS.len())
+            .filter_map(|i| Some((i, self.heads[i].as_ref()?.time_stamp())))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b));
+        match earliest {
+            Some((i, _)) => Poll::Ready((KINDS[i].0, self.heads[i].take().unwrap())),
+            None => Poll::Pending,
+        }
+    }
+    fn capture(&self, pointer_id: i32) {
+        let _ = self.element.set_pointer_capture(pointer_id);
+    }
+    fn release(&self, pointer_id: i32) {
+        if self.element.has_pointer_capture(pointer_id) {
+            let _ = self.element.release_pointer_capture(pointer_id);
+        }
+    }
+}
+
+fn position(ev: &PointerEvent) -> (f64, f64) {
+    (ev.client_x() as f64, ev.client_y() as f64)
+}
+
+fn is_primary_press(ev: &PointerEvent) -> bool {
+    ev.is_primary() && ev.button() == 0
+}
+
+fn distance((x1, y1): (f64, f64), (x2, y2): (f64, f64)) -> f64 {
+    (x2 - x1).hypot(y2 - y1)
+}
+
+/// Stream returned by [until_drag_gesture][EmitGesture::until_drag_gesture].
+pub struct DragGestureStream {
+    input: PointerInput,
+    threshold: f64,
+    state: DragState,
+}
+
+enum DragState {
+    Idle,
+    Pressed {
+        pointer_id: i32,
+        start: (f64, f64),
+    },
+    Dragging {
+        pointer_id: i32,
+        start: (f64, f64),
+        last: (f64, f64),
+    },
+}
+
+impl DragGestureStream {
+    /// Set how far (in CSS pixels) the pointer has to move before the drag
+    /// starts. The default is 4.
+    pub fn set_threshold(&mut self, threshold: f64) {
+        self.threshold = threshold;
+    }
+}
+
+impl Stream for DragGestureStream {
+    type Item = DragGesture;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            let (kind, ev) = match this.input.poll_next(cx) {
+                Poll::Ready(x) => x,
+                Poll::Pending => return Poll::Pending,
+            };
+            let id = ev.pointer_id();
+            let pos = position(&ev);
+            let (phase, start, last) = match (&this.state, kind) {
+                (DragState::Idle, Kind::Down) if is_primary_press(&ev) => {
+                    this.state = DragState::Pressed {
+                        pointer_id: id,
+                        start: pos,
+                    };
+                    continue;
+                }
+                (&DragState::Pressed { pointer_id, start }, Kind::Move) if pointer_id == id => {
+                    if distance(start, pos) <= this.threshold {
+                        continue;
+                    }
+                    this.input.capture(id);
+                    (GesturePhase::Start, start, start)
+                }
+                (&DragState::Pressed { pointer_id, .. }, Kind::Up | Kind::Cancel | Kind::Leave)
+                    if pointer_id == id =>
+                {
+                    this.state = DragState::Idle;
+                    continue;
+                }
+                (
+                    &DragState::Dragging {
+                        pointer_id,
+                        start,
+                        last,
+                    },
+                    Kind::Move | Kind::Up | Kind::Cancel,
+                ) if pointer_id == id => {
+                    let phase = match kind {
+                        Kind::Move => GesturePhase::Move,
+                        Kind::Up => GesturePhase::End,
+                        _ => GesturePhase::Cancel,
+                    };
+                    (phase, start, last)
+                }
+                _ => continue,
+            };
+            this.state = match phase {
+                GesturePhase::Start | GesturePhase::Move => DragState::Dragging {
+                    pointer_id: id,
+                    start,
+                    last: pos,
+                },
+                GesturePhase::End | GesturePhase::Cancel => DragState::Idle,
+            };
+            return Poll::Ready(Some(DragGesture {
+                phase,
+                x: pos.0,
+                y: pos.1,
+                dx: pos.0 - start.0,
+                dy: pos.1 - start.1,
+                step_x: pos.0 - last.0,
+                step_y: pos.1 - last.1,
+                event: ev,
+            }));
+        }
+    }
+}
+
+impl Drop for DragGestureStream {
+    fn drop(&mut self) {
+        if let DragState::Dragging { pointer_id, .. } = self.state {
+            self.input.release(pointer_id);
+        }
+    }
+}
+
+/// Stream returned by [until_pinch][EmitGesture::until_pinch].
+pub struct PinchStream {
+    input: PointerInput,
+    /// The (up to two) pointers pressed on the element and their positions.
+    pointers: Vec<(i32, (f64, f64))>,
+    /// Distance and angle between the pointers when the pinch started,
+    /// and the latest item.
+    pinch: Option<(f64, f64, PinchGesture)>,
+}
+
+impl PinchStream {
+    fn measure(&self) -> (f64, f64, f64, f64) {
+        let (_, (x1, y1)) = self.pointers[0];
+        let (_, (x2, y2)) = self.pointers[1];
+        (
+            distance((x1, y1), (x2, y2)),
+            (y2 - y1).atan2(x2 - x1),
+            (x1 + x2) / 2.0,
+            (y1 + y2) / 2.0,
+        )
+    }
+}
+
+impl Stream for PinchStream {
+    type Item = PinchGesture;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            let (kind, ev) = match this.input.poll_next(cx) {
+                Poll::Ready(x) => x,
+                Poll::Pending => return Poll::Pending,
+            };
+            let id = ev.pointer_id();
+            let tracked = this.pointers.iter().position(|(p, _)| *p == id);
+            match (kind, tracked) {
+                (Kind::Down, None) if this.pointers.len() < 2 => {
+                    this.pointers.push((id, position(&ev)));
+                    if this.pointers.len() == 2 {
+                        this.pointers
+                            .iter()
+                            .for_each(|(p, _)| this.input.capture(*p));
+                        let (dist, angle, center_x, center_y) = this.measure();
+                        let item = PinchGesture {
+                            phase: GesturePhase::Start,
+                            scale: 1.0,
+                            rotation: 0.0,
+                            center_x,
+                            center_y,
+                        };
+                        this.pinch = Some((dist, angle, item.clone()));
+                        return Poll::Ready(Some(item));
+                    }
+                }
+                (Kind::Move, Some(index)) => {
+                    this.pointers[index].1 = position(&ev);
+                    if let Some((start_dist, start_angle, _)) = this.pinch {
+                        let (dist, angle, center_x, center_y) = this.measure();
+                        let item = PinchGesture {
+                            phase: GesturePhase::Move,
+                            scale: if start_dist > 0.0 {
+                                dist / start_dist
+                            } else {
+                                1.0
+                            },
+                            rotation: normalize_angle(angle - start_angle),
+                            center_x,
+                            center_y,
+                        };
+                        this.pinch = Some((start_dist, start_angle, item.clone()));
+                        return Poll::Ready(Some(item));
+                    }
+                }
+                (Kind::Up | Kind::Cancel | Kind::Leave, Some(index)) => {
+                    if kind == Kind::Leave && this.pinch.is_some() {
+                        // captured pointers get `pointerleave` when released;
+                        // wait for the `pointerup` instead
+                        continue;
+                    }
+                    this.pointers.remove(index);
+                    if let Some((_, _, mut item)) = this.pinch.take() {
+                        this.pointers
+                            .iter()
+                            .for_each(|(p, _)| this.input.release(*p));
+                        item.phase = if kind == Kind::Up {
+                            GesturePhase::End
+                        } else {
+                            GesturePhase::Cancel
+                        };
+                        return Poll::Ready(Some(item));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Drop for PinchStream {
+    fn drop(&mut self) {
+        if self.pinch.is_some() {
+            self.pointers
+                .iter()
+                .for_each(|(p, _)| self.input.release(*p));
+        }
+    }
+}
+
+/// Bring an angle (in radians) into the range -π to π.
+fn normalize_angle(angle: f64) -> f64 {
+    let angle = angle.rem_euclid(2.0 * PI);
+    if angle > PI {
+        angle - 2.0 * PI
+    } else {
+        angle
+    }
+}
+
+/// Stream returned by [until_long_press][EmitGesture::until_long_press].
+pub struct LongPressStream {
+    input: PointerInput,
+    threshold: f64,
+    duration: Duration,
+    pressed: Option<(PointerEvent, TimeoutFuture)>,
+}
+
+impl LongPressStream {
+    /// Set how long the pointer has to be held down. The default is 500 ms.
+    pub fn set_duration(&mut self, duration: Duration) {
+        self.duration = duration;
+    }
+    /// Set how far (in CSS pixels) the pointer can move before the press is
+    /// abandoned. The default is 4.
+    pub fn set_threshold(&mut self, threshold: f64) {
+        self.threshold = threshold;
+    }
+}
+
+impl Stream for LongPressStream {
+    type Item = LongPress;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some((_, timer)) = &mut this.pressed {
+                if Pin::new(timer).poll(cx).is_ready() {
+                    let (event, _) = this.pressed.take().unwrap();
+                    let (x, y) = position(&event);
+                    return Poll::Ready(Some(LongPress { x, y, event }));
+                }
+            }
+            let (kind, ev) = match this.input.poll_next(cx) {
+                Poll::Ready(x) => x,
+                Poll::Pending => return Poll::Pending,
+            };
+            match (&this.pressed, kind) {
+                (None, Kind::Down) if is_primary_press(&ev) => {
+                    let millis = this.duration.as_millis().try_into().unwrap_or(u32::MAX);
+                    this.pressed = Some((ev, TimeoutFuture::new(millis)));
+                }
+                (Some((down, _)), _) if down.pointer_id() == ev.pointer_id() => {
+                    let abandon = match kind {
+                        Kind::Move => distance(position(down), position(&ev)) > this.threshold,
+                        _ => true,
+                    };
+                    if abandon {
+                        this.pressed = None;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Stream returned by [until_swipe][EmitGesture::until_swipe].
+pub struct SwipeStream {
+    input: PointerInput,
+    threshold: f64,
+    min_distance: f64,
+    min_velocity: f64,
+    /// The `pointerdown` event, and whether the pointer has been captured.
+    pressed: Option<(PointerEvent, bool)>,
+}
+
+impl SwipeStream {
+    /// Set how far (in CSS pixels) the pointer has to travel for a swipe.
+    /// The default is 30.
+    pub fn set_min_distance(&mut self, min_distance: f64) {
+        self.min_distance = min_distance;
+    }
+    /// Set how fast (in CSS pixels per millisecond) the pointer has to travel
+    /// for a swipe. The default is 0.3.
+    pub fn set_min_velocity(&mut self, min_velocity: f64) {
+        self.min_velocity = min_velocity;
+    }
+}
+
+impl Stream for SwipeStream {
+    type Item = Swipe;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            let (kind, ev) = match this.input.poll_next(cx) {
+                Poll::Ready(x) => x,
+                Poll::Pending => return Poll::Pending,
+            };
+            let id = ev.pointer_id();
+            match (&mut this.pressed, kind) {
+                (None, Kind::Down) if is_primary_press(&ev) => {
+                    this.pressed = Some((ev, false));
+                }
+                (Some((down, captured)), Kind::Move)
+                    if down.pointer_id() == id
+                        && !*captured
+                        && distance(position(down), position(&ev)) > this.threshold =>
+                {
+                    *captured = true;
+                    this.input.capture(id);
+                }
+                (Some((down, captured)), Kind::Up) if down.pointer_id() == id => {
+                    let (x0, y0) = position(down);
+                    let (x1, y1) = position(&ev);
+                    let elapsed = ev.time_stamp() - down.time_stamp();
+                    let (dx, dy) = (x1 - x0, y1 - y0);
+                    let was_captured = *captured;
+                    this.pressed = None;
+                    if was_captured {
+                        this.input.release(id);
+                    }
+                    let direction = swipe_direction(dx, dy);
+                    let travel = dx.abs().max(dy.abs());
+                    let velocity = travel / elapsed.max(1.0);
+                    if travel >= this.min_distance && velocity >= this.min_velocity {
+                        return Poll::Ready(Some(Swipe {
+                            direction,
+                            dx,
+                            dy,
+                            velocity,
+                            event: ev,
+                        }));
+                    }
+                }
+                // a captured pointer leaves when it is released,
+                // after the `pointerup`, so only uncaptured leaves count
+                (Some((down, captured)), Kind::Cancel | Kind::Leave)
+                    if down.pointer_id() == id && (!*captured || kind == Kind::Cancel) =>
+                {
+                    if *captured {
+                        this.input.release(id);
+                    }
+                    this.pressed = None;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Drop for SwipeStream {
+    fn drop(&mut self) {
+        if let Some((down, true)) = &self.pressed {
+            self.input.release(down.pointer_id());
+        }
+    }
+}
+
+/// The direction of the dominant axis of a movement.
+fn swipe_direction(dx: f64, dy: f64) -> SwipeDirection {
+    if dx.abs() >= dy.abs() {
+        if dx < 0.0 {
+            SwipeDirection::Left
+        } else {
+            SwipeDirection::Right
+        }
+    } else if dy < 0.0 {
+        SwipeDirection::Up
+    } else {
+        SwipeDirection::Down
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directions() {
+        assert_eq!(swipe_direction(-40.0, 10.0), SwipeDirection::Left);
+        assert_eq!(swipe_direction(40.0, -39.0), SwipeDirection::Right);
+        assert_eq!(swipe_direction(3.0, -50.0), SwipeDirection::Up);
+        assert_eq!(swipe_direction(-3.0, 50.0), SwipeDirection::Down);
+    }
+
+    #[test]
+    fn angles() {
+        assert!((normalize_angle(0.5) - 0.5).abs() < 1e-9);
+        assert!((normalize_angle(2.0 * PI - 0.5) + 0.5).abs() < 1e-9);
+        assert!((normalize_angle(-2.0 * PI + 0.5) - 0.5).abs() < 1e-9);
+        assert!((normalize_angle(PI + 0.5) - (0.5 - PI)).abs() < 1e-9);
+    }
+}
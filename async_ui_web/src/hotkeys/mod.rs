@@ -0,0 +1,447 @@
+//! App-wide keyboard shortcuts.
+//!
+//! All shortcuts share a single `keydown` listener on the document.
+//!
+//! ```
+//! # use async_ui_web::hotkeys::until_hotkey;
+//! # use futures_lite::StreamExt;
+//! # let _ = async {
+//! let mut save = until_hotkey("Ctrl+S");
+//! let mut go_to_inbox = until_hotkey("g i");
+//! loop {
+//!     save.next().await;
+//!     // save the document
+//! }
+//! # };
+//! ```
+//!
+//! ### Scopes
+//! Shortcuts can be registered in a [HotkeyScope]. A scope is active while
+//! the future passed to its [activate][HotkeyScope::activate] method is alive.
+//! A scope created with [new_focus_within][HotkeyScope::new_focus_within]
+//! is additionally only active while the keyboard focus is inside an element.
+//!
+//! When more than one active scope has a shortcut matching the pressed keys,
+//! the innermost scope wins. A scope activated inside the future of
+//! another scope is inner to it. Among scopes at the same level, the one
+//! activated last wins. Shortcuts registered with the free function
+//! [until_hotkey] are in the outermost scope.
+//!
+//! Within the winning scope, longer sequences win over shorter ones
+//! (so `"g i"` beats `"i"`).
+//!
+//! ### Notes
+//! *   Shortcuts without Ctrl, Alt, or Meta are ignored while the user is
+//!     typing in an `<input>`, `<textarea>`, `<select>`, or editable element.
+//! *   The chords in a sequence must be pressed within one second of each other.
+//! *   `preventDefault` is called on the `keydown` event of a matched shortcut.
+
+mod shortcut;
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    future::{poll_fn, Future},
+    marker::PhantomData,
+    pin::{pin, Pin},
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use async_ui_internal_utils::dummy_waker::dummy_waker;
+use async_ui_web_core::window::DOCUMENT;
+use futures_lite::Stream;
+use wasm_bindgen::{prelude::Closure, JsCast, UnwrapThrowExt};
+use web_sys::{Element, HtmlElement, KeyboardEvent};
+
+pub use shortcut::{Chord, ParseShortcutError, Shortcut};
+
+/// Maximum time between the chords of a sequence, in milliseconds.
+const SEQUENCE_TIMEOUT: f64 = 1000.0;
+/// The scope of shortcuts created with the [until_hotkey] function.
+const GLOBAL_SCOPE: u64 = 0;
+
+thread_local! {
+    static MANAGER: RefCell<Manager> = RefCell::new(Manager::new());
+    /// Depth of the innermost scope whose future is being polled right now.
+    static CURRENT_DEPTH: Cell<u32> = const { Cell::new(0) };
+}
+
+fn with_manager<R>(func: impl FnOnce(&mut Manager) -> R) -> R {
+    MANAGER.with(|m| func(&mut m.borrow_mut()))
+}
+
+type Slot = Rc<RefCell<(Option<KeyboardEvent>, Waker)>>;
+
+struct Manager {
+    scopes: HashMap<u64, ScopeState>,
+    listeners: Vec<Listener>,
+    next_id: u64,
+    history: Vec<KeyPress>,
+    last_press: f64,
+    closure: Option<Closure<dyn Fn(KeyboardEvent)>>,
+}
+
+struct ScopeState {
+    /// `(depth, activation order)`, or None if not active.
+    activation: Option<(u32, u64)>,
+    region: Option<Element>,
+}
+
+struct Listener {
+    id: u64,
+    scope: u64,
+    shortcut: Shortcut,
+    slot: Slot,
+}
+
+struct KeyPress {
+    key: String,
+    ctrl: bool,
+    alt: bool,
+    shift: bool,
+    meta: bool,
+}
+
+impl Manager {
+    fn new() -> Self {
+        let mut scopes = HashMap::new();
+        scopes.insert(
+            GLOBAL_SCOPE,
+            ScopeState {
+                activation: Some((0, 0)),
+                region: None,
+            },
+        );
+        Self {
+            scopes,
+            listeners: Vec::new(),
+            next_id: GLOBAL_SCOPE + 1,
+            history: Vec::new(),
+            last_press: f64::NEG_INFINITY,
+            closure: None,
+        }
+    }
+    fn new_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+    fn add_listener(&mut self, scope: u64, shortcut: Shortcut) -> (u64, Slot) {
+        let id = self.new_id();
+        let slot = Rc::new(RefCell::new((None, dummy_waker())));
+        self.listeners.push(Listener {
+            id,
+            scope,
+            shortcut,
+            slot: slot.clone(),
+        });
+        if self.closure.is_none() {
+            let closure = Closure::new(on_keydown);
+            DOCUMENT.with(|doc| {
+                doc.add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())
+                    .unwrap_throw()
+            });
+            self.closure = Some(closure);
+        }
+        (id, slot)
+    }
+    fn remove_listener(&mut self, id: u64) {
+        self.listeners.retain(|l| l.id != id);
+        if self.listeners.is_empty() {
+            if let Some(closure) = self.closure.take() {
+                DOCUMENT.with(|doc| {
+                    doc.remove_event_listener_with_callback(
+                        "keydown",
+                        closure.as_ref().unchecked_ref(),
+                    )
+                    .unwrap_throw()
+                });
+            }
+        }
+    }
+    /// Find the listeners that should receive this key press.
+    fn handle(&mut self, ev: &KeyboardEvent) -> Vec<Slot> {
+        let time = ev.time_stamp();
+        if time - self.last_press > SEQUENCE_TIMEOUT {
+            self.history.clear();
+        }
+        self.last_press = time;
+        self.history.push(KeyPress {
+            key: ev.key(),
+            ctrl: ev.ctrl_key(),
+            alt: ev.alt_key(),
+            shift: ev.shift_key(),
+            meta: ev.meta_key(),
+        });
+        let max_len = self
+            .listeners
+            .iter()
+            .map(|l| l.shortcut.chords.len())
+            .max()
+            .unwrap_or(0);
+        if self.history.len() > max_len {
+            self.history.drain(..self.history.len() - max_len);
+        }
+
+        let typing = ev.target().is_some_and(|t| is_editable(&t));
+        let focused = DOCUMENT.with(|doc| doc.active_element());
+        let mut winner: Option<((u32, u64, usize), &Shortcut)> = None;
+        let mut matched = Vec::new();
+        for listener in self.listeners.iter() {
+            let Some(scope) = self.scopes.get(&listener.scope) else {
+                continue;
+            };
+            let Some((depth, order)) = scope.activation else {
+                continue;
+            };
+            if let Some(region) = scope.region.as_ref() {
+                if !region.contains(focused.as_ref().map(AsRef::as_ref)) {
+                    continue;
+                }
+            }
+            let chords = &listener.shortcut.chords;
+            if chords.len() > self.history.len()
+                || (typing && !chords.last().unwrap().has_command_modifier())
+            {
+                continue;
+            }
+            let presses = &self.history[self.history.len() - chords.len()..];
+            let is_match = chords.iter().zip(presses).all(|(chord, press)| {
+                chord.matches(&press.key, press.ctrl, press.alt, press.shift, press.meta)
+            });
+            if !is_match {
+                continue;
+            }
+            let priority = (depth, order, chords.len());
+            match winner {
+                Some((best, _)) if best > priority => continue,
+                Some((best, shortcut)) if best == priority && *shortcut == listener.shortcut => {}
+                _ => {
+                    winner = Some((priority, &listener.shortcut));
+                    matched.clear();
+                }
+            }
+            matched.push(listener.slot.clone());
+        }
+        if !matched.is_empty() {
+            self.history.clear();
+        }
+        matched
+    }
+}
+
+fn on_keydown(ev: KeyboardEvent) {
+    if ev.is_composing() || matches!(ev.key().as_str(), "Control" | "Shift" | "Alt" | "Meta") {
+        return;
+    }
+    let matched = with_manager(|m| m.handle(&ev));
+    if matched.is_empty() {
+        return;
+    }
+    ev.prevent_default();
+    for slot in matched {
+        let mut slot = slot.borrow_mut();
+        slot.0 = Some(ev.clone());
+        slot.1.wake_by_ref();
+    }
+    async_ui_web_core::executor::run_now();
+}
+
+fn is_editable(target: &web_sys::EventTarget) -> bool {
+    let Some(element) = target.dyn_ref::<HtmlElement>() else {
+        return false;
+    };
+    matches!(element.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT")
+        || element.is_content_editable()
+}
+
+/// A group of shortcuts that are only active some of the time.
+///
+/// See the [module documentation][self] for how scopes work.
+///
+/// ```
+/// # use async_ui_web::{hotkeys::HotkeyScope, html::Div, join, NoChild};
+/// # let _ = async {
+/// let editor = Div::new();
+/// // Shortcuts that only work while focus is in the editor.
+/// let scope = HotkeyScope::new_focus_within(&editor);
+/// scope
+///     .activate(join((editor.render(NoChild), async {
+///         loop {
+///             scope.until_hotkey("Ctrl+B").await;
+///             // toggle bold
+///         }
+///     })))
+///     .await;
+/// # };
+/// ```
+pub struct HotkeyScope {
+    id: u64,
+}
+
+impl Default for HotkeyScope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HotkeyScope {
+    /// Create a scope that is active while its [activate][Self::activate]
+    /// future is alive.
+    pub fn new() -> Self {
+        Self::new_with_region(None)
+    }
+    /// Create a scope that is active while its [activate][Self::activate]
+    /// future is alive *and* the keyboard focus is inside the given element.
+    pub fn new_focus_within(region: &impl AsRef<Element>) -> Self {
+        Self::new_with_region(Some(region.as_ref().clone()))
+    }
+    fn new_with_region(region: Option<Element>) -> Self {
+        let id = with_manager(|m| {
+            let id = m.new_id();
+            m.scopes.insert(
+                id,
+                ScopeState {
+                    activation: None,
+                    region,
+                },
+            );
+            id
+        });
+        Self { id }
+    }
+    /// Activate this scope while the given future is running.
+    ///
+    /// Scopes activated from inside the given future will be inner to this
+    /// scope and have priority over it.
+    ///
+    /// This method should only be called once at a time. It may misbehave otherwise.
+    pub async fn activate<F: Future>(&self, future: F) -> F::Output {
+        let mut future = pin!(future);
+        let _guard = scopeguard::guard((), |_| {
+            with_manager(|m| {
+                if let Some(scope) = m.scopes.get_mut(&self.id) {
+                    scope.activation = None;
+                }
+            })
+        });
+        let mut depth = None;
+        poll_fn(|cx| {
+            let depth = *depth.get_or_insert_with(|| {
+                let depth = CURRENT_DEPTH.with(Cell::get) + 1;
+                with_manager(|m| {
+                    let order = m.new_id();
+                    if let Some(scope) = m.scopes.get_mut(&self.id) {
+                        scope.activation = Some((depth, order));
+                    }
+                });
+                depth
+            });
+            let parent = CURRENT_DEPTH.with(|d| d.replace(depth));
+            let res = future.as_mut().poll(cx);
+            CURRENT_DEPTH.with(|d| d.set(parent));
+            res
+        })
+        .await
+    }
+    /// Wait until the given shortcut is pressed while this scope is active.
+    /// The return type is both a [Future] and a [Stream] that yields the
+    /// `keydown` event of the last chord.
+    ///
+    /// See [Shortcut] for the syntax.
+    ///
+    /// **Panics** if the shortcut is invalid.
+    /// Use [until_shortcut][Self::until_shortcut] to handle errors yourself.
+    pub fn until_hotkey(&self, shortcut: &str) -> HotkeyStream<'_> {
+        self.until_shortcut(parse_or_panic(shortcut))
+    }
+    /// Like [until_hotkey][Self::until_hotkey], but takes an already-parsed [Shortcut].
+    pub fn until_shortcut(&self, shortcut: Shortcut) -> HotkeyStream<'_> {
+        HotkeyStream::new(self.id, shortcut)
+    }
+}
+
+impl Drop for HotkeyScope {
+    fn drop(&mut self) {
+        with_manager(|m| m.scopes.remove(&self.id));
+    }
+}
+
+/// Wait until the given shortcut is pressed.
+/// The return type is both a [Future] and a [Stream] that yields the
+/// `keydown` event of the last chord.
+///
+/// The shortcut is in the outermost scope, so it loses to shortcuts in any
+/// active [HotkeyScope].
+///
+/// See [Shortcut] for the syntax.
+///
+/// **Panics** if the shortcut is invalid.
+pub fn until_hotkey(shortcut: &str) -> HotkeyStream<'static> {
+    HotkeyStream::new(GLOBAL_SCOPE, parse_or_panic(shortcut))
+}
+
+fn parse_or_panic(shortcut: &str) -> Shortcut {
+    match shortcut.parse() {
+        Ok(parsed) => parsed,
+        Err(e) => panic!("invalid shortcut `{shortcut}`: {e}"),
+    }
+}
+
+/// A struct implementing both [Future] and [Stream].
+/// Yields the `keydown` event that completed the shortcut.
+///
+/// Get one from [until_hotkey] or [HotkeyScope::until_hotkey].
+///
+/// The stream is never exhausted.
+/// Like [EventFutureStream][crate::event_handling::EventFutureStream],
+/// it only keeps the last event.
+pub struct HotkeyStream<'s> {
+    id: u64,
+    slot: Slot,
+    _phantom: PhantomData<&'s HotkeyScope>,
+}
+
+impl<'s> HotkeyStream<'s> {
+    fn new(scope: u64, shortcut: Shortcut) -> Self {
+        let (id, slot) = with_manager(|m| m.add_listener(scope, shortcut));
+        Self {
+            id,
+            slot,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'s> Stream for HotkeyStream<'s> {
+    type Item = KeyboardEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut slot = self.slot.borrow_mut();
+        if !slot.1.will_wake(cx.waker()) {
+            slot.1 = cx.waker().to_owned();
+        }
+        match slot.0.take() {
+            Some(ev) => Poll::Ready(Some(ev)),
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl<'s> Future for HotkeyStream<'s> {
+    type Output = KeyboardEvent;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.poll_next(cx) {
+            Poll::Ready(Some(ev)) => Poll::Ready(ev),
+            _ => Poll::Pending,
+        }
+    }
+}
+
+impl<'s> Drop for HotkeyStream<'s> {
+    fn drop(&mut self) {
+        with_manager(|m| m.remove_listener(self.id));
+    }
+}
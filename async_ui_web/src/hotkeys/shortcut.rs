@@ -0,0 +1,246 @@
+use std::{fmt::Display, str::FromStr};
+
+/// A keyboard shortcut: one or more [Chord]s to be pressed in sequence.
+///
+/// Parse it from a string. Chords are separated by spaces,
+/// and keys within a chord are joined with `+`.
+///
+/// ```
+/// # use async_ui_web::hotkeys::Shortcut;
+/// let save: Shortcut = "Ctrl+S".parse().unwrap();
+/// let go_to_inbox: Shortcut = "g i".parse().unwrap();
+/// let close_all: Shortcut = "Ctrl+K Ctrl+W".parse().unwrap();
+/// ```
+///
+/// Modifier names are case-insensitive:
+/// `Ctrl` (or `Control`), `Alt` (or `Option`), `Shift`,
+/// and `Meta` (or `Cmd`, `Command`, `Super`, `Win`).
+///
+/// The key is matched against [KeyboardEvent.key](https://developer.mozilla.org/en-US/docs/Web/API/KeyboardEvent/key),
+/// case-insensitively. Some aliases are accepted: `Space`, `Esc`, `Return`,
+/// `Del`, `Up`, `Down`, `Left`, `Right`, and `Plus`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+    pub(super) chords: Vec<Chord>,
+}
+
+/// A key together with the modifiers that must be held while pressing it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Chord {
+    /// The key, lowercased.
+    key: String,
+    ctrl: bool,
+    alt: bool,
+    shift: bool,
+    meta: bool,
+}
+
+/// The error returned when parsing an invalid [Shortcut].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseShortcutError {
+    /// The string contains no chord.
+    Empty,
+    /// A chord has only modifiers and no key, like `"Ctrl+"`.
+    MissingKey(String),
+    /// A chord has more than one non-modifier key, like `"A+B"`.
+    MultipleKeys(String),
+}
+
+impl Display for ParseShortcutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "empty shortcut"),
+            Self::MissingKey(chord) => write!(f, "no key in chord `{chord}`"),
+            Self::MultipleKeys(chord) => write!(f, "more than one key in chord `{chord}`"),
+        }
+    }
+}
+
+impl std::error::Error for ParseShortcutError {}
+
+impl FromStr for Shortcut {
+    type Err = ParseShortcutError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chords = s
+            .split_whitespace()
+            .map(Chord::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        if chords.is_empty() {
+            Err(ParseShortcutError::Empty)
+        } else {
+            Ok(Self { chords })
+        }
+    }
+}
+
+impl FromStr for Chord {
+    type Err = ParseShortcutError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chord = Chord {
+            key: String::new(),
+            ctrl: false,
+            alt: false,
+            shift: false,
+            meta: false,
+        };
+        // "Ctrl++" means Ctrl and the plus key
+        let (rest, plus_key) = match s.strip_suffix("++") {
+            Some(rest) => (rest, true),
+            None if s == "+" => ("", true),
+            None => (s, false),
+        };
+        let mut key = plus_key.then(|| String::from("+"));
+        for part in rest.split('+').filter(|part| !part.is_empty()) {
+            match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => chord.ctrl = true,
+                "alt" | "option" => chord.alt = true,
+                "shift" => chord.shift = true,
+                "meta" | "cmd" | "command" | "super" | "win" => chord.meta = true,
+                _ if key.is_some() => return Err(ParseShortcutError::MultipleKeys(s.into())),
+                _ => key = Some(normalize_key(part)),
+            }
+        }
+        chord.key = key.ok_or_else(|| ParseShortcutError::MissingKey(s.into()))?;
+        Ok(chord)
+    }
+}
+
+/// Lowercase the key name and resolve aliases.
+fn normalize_key(key: &str) -> String {
+    let key = key.to_lowercase();
+    match key.as_str() {
+        "space" => " ",
+        "esc" => "escape",
+        "return" => "enter",
+        "del" => "delete",
+        "up" => "arrowup",
+        "down" => "arrowdown",
+        "left" => "arrowleft",
+        "right" => "arrowright",
+        "plus" => "+",
+        _ => return key,
+    }
+    .into()
+}
+
+impl Chord {
+    /// Check if a key press matches this chord.
+    ///
+    /// `key` is the [KeyboardEvent.key](https://developer.mozilla.org/en-US/docs/Web/API/KeyboardEvent/key) value.
+    ///
+    /// For symbol keys (like `?` or `+`), the Shift state is ignored,
+    /// since typing them often requires Shift anyway.
+    pub fn matches(&self, key: &str, ctrl: bool, alt: bool, shift: bool, meta: bool) -> bool {
+        let mut chars = self.key.chars();
+        let is_symbol = matches!(
+            (chars.next(), chars.next()),
+            (Some(c), None) if !c.is_alphanumeric() && c != ' '
+        );
+        self.ctrl == ctrl
+            && self.alt == alt
+            && self.meta == meta
+            && (is_symbol || self.shift == shift)
+            && key.to_lowercase() == self.key
+    }
+    /// Check if this chord holds Ctrl, Alt, or Meta.
+    ///
+    /// Only such chords fire while the user is typing in a text field.
+    pub fn has_command_modifier(&self) -> bool {
+        self.ctrl || self.alt || self.meta
+    }
+}
+
+impl Display for Chord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (held, name) in [
+            (self.ctrl, "Ctrl+"),
+            (self.alt, "Alt+"),
+            (self.shift, "Shift+"),
+            (self.meta, "Meta+"),
+        ] {
+            if held {
+                f.write_str(name)?;
+            }
+        }
+        match self.key.as_str() {
+            " " => f.write_str("Space"),
+            key => {
+                let mut chars = key.chars();
+                if let Some(first) = chars.next() {
+                    write!(f, "{}{}", first.to_uppercase(), chars.as_str())?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Display for Shortcut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx, chord) in self.chords.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(" ")?;
+            }
+            chord.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(s: &str) -> Chord {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_chords() {
+        let c = chord("Ctrl+Shift+S");
+        assert!(c.ctrl && c.shift && !c.alt && !c.meta);
+        assert_eq!(c.key, "s");
+        assert_eq!(chord("cmd+K"), chord("Meta+k"));
+        assert_eq!(chord("Ctrl++").key, "+");
+        assert_eq!(chord("+").key, "+");
+        assert_eq!(chord("Esc").key, "escape");
+        assert_eq!(chord("Space").key, " ");
+    }
+
+    #[test]
+    fn parse_sequences() {
+        let s: Shortcut = "g  i".parse().unwrap();
+        assert_eq!(s.chords, [chord("g"), chord("i")]);
+        assert_eq!(s.to_string(), "G I");
+        let s: Shortcut = "Ctrl+K Ctrl+W".parse().unwrap();
+        assert_eq!(s.to_string(), "Ctrl+K Ctrl+W");
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!("".parse::<Shortcut>(), Err(ParseShortcutError::Empty));
+        assert_eq!(
+            "Ctrl+".parse::<Shortcut>(),
+            Err(ParseShortcutError::MissingKey("Ctrl+".into()))
+        );
+        assert_eq!(
+            "a+b".parse::<Shortcut>(),
+            Err(ParseShortcutError::MultipleKeys("a+b".into()))
+        );
+    }
+
+    #[test]
+    fn matching() {
+        let c = chord("Ctrl+S");
+        assert!(c.matches("s", true, false, false, false));
+        assert!(c.matches("S", true, false, false, false));
+        assert!(!c.matches("s", true, false, true, false));
+        assert!(!c.matches("s", false, false, false, false));
+        // shift is ignored for symbols
+        assert!(chord("?").matches("?", false, false, true, false));
+        assert!(!chord("a").matches("A", false, false, true, false));
+        assert!(chord("Shift+A").matches("A", false, false, true, false));
+    }
+}
@@ -5,12 +5,25 @@ See the [guide book](https://wishawa.github.io/async_ui/book/) to get started!
 
 */
 
+pub mod accessibility;
+pub mod bind;
 pub mod components;
+pub mod custom_element;
 pub mod executor;
+pub mod focus;
+pub mod form;
+pub mod gestures;
+pub mod hotkeys;
 pub mod lists;
+pub mod media;
 mod mount;
 mod no_child;
+pub mod observers;
+pub mod positioning;
+pub mod reactive;
+pub mod router;
 mod shortcuts;
+pub mod style;
 
 pub use async_ui_internal_utils::reactive_cell::ReactiveCell;
 pub use async_ui_web_core::combinators::{join, race, race_ok, try_join};
@@ -31,13 +44,19 @@ pub mod event_handling {
     Types used in event handling mechanism.
     You shouldn't need to interact with this module directly often.
     */
-    pub use async_ui_web_html::events::EventFutureStream;
+    pub use async_ui_web_html::events::{
+        DelegatedEventStream, EventBuffer, EventDelegator, EventFutureStream,
+        DELEGATE_KEY_ATTRIBUTE,
+    };
 }
 
 pub mod event_traits {
     /*!
     Traits for event handling.
     */
+    pub use super::focus::EmitFocus;
+    pub use super::gestures::EmitGesture;
+    pub use super::observers::ObserveElement;
     pub use async_ui_web_html::events::{EmitElementEvent, EmitEvent, EmitHtmlElementEvent};
 }
 
@@ -45,7 +64,13 @@ pub mod shortcut_traits {
     /*!
     Traits provided for convenience.
      */
-    pub use super::shortcuts::{ShortcutClassList, ShortcutClassListBuilder, ShortcutRenderStr};
+    pub use super::accessibility::ShortcutAria;
+    pub use super::media::ShortcutMedia;
+    pub use super::shortcuts::{
+        ShortcutClassList, ShortcutClassListBuilder, ShortcutRenderReactive, ShortcutRenderStr,
+        ShortcutRenderStream,
+    };
+    pub use super::style::ShortcutStyle;
     pub use async_ui_web_core::combinators::UiFutureExt;
 }
 
@@ -57,9 +82,16 @@ pub mod prelude_traits {
     use async_ui_web::prelude_traits::*;
     ```
      */
+    pub use super::accessibility::ShortcutAria as _;
+    pub use super::focus::EmitFocus as _;
+    pub use super::gestures::EmitGesture as _;
+    pub use super::media::ShortcutMedia as _;
+    pub use super::observers::ObserveElement as _;
     pub use super::shortcuts::{
-        ShortcutClassList as _, ShortcutClassListBuilder as _, ShortcutRenderStr as _,
+        ShortcutClassList as _, ShortcutClassListBuilder as _, ShortcutRenderReactive as _,
+        ShortcutRenderStr as _, ShortcutRenderStream as _,
     };
+    pub use super::style::ShortcutStyle as _;
     pub use async_ui_web_core::combinators::UiFutureExt as _;
     pub use async_ui_web_html::events::{
         EmitElementEvent as _, EmitEvent as _, EmitHtmlElementEvent as _,
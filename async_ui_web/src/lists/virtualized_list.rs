@@ -10,9 +10,10 @@ use async_ui_internal_utils::reactive_cell::ReactiveCell;
 use async_ui_web_core::{combinators::join, ContainerNodeFuture};
 use futures_lite::{Future, StreamExt};
 use wasm_bindgen::{prelude::Closure, JsCast, JsValue, UnwrapThrowExt};
-use web_sys::{Element, HtmlElement, IntersectionObserver, IntersectionObserverInit};
+use web_sys::HtmlElement;
 
 use super::DynamicList;
+use crate::observers::{IntersectionOptions, ObserveElement};
 
 /**
 For displaying large lists.
@@ -148,11 +149,14 @@ impl<'c, Fut: Future + 'c, Renderer: FnMut(usize) -> Fut> VirtualizedList<'c, Fu
                 .unwrap_throw();
         });
         join((spf_render, self.list.render(), spb_render, async {
-            let _observer = Observer::new(
-                self.root,
-                &[spf, spb],
-                self.wake_closure.as_ref().unchecked_ref(),
-            );
+            let options = IntersectionOptions {
+                root: Some(self.root.clone().into()),
+                root_margin: "100%".into(),
+                ..Default::default()
+            };
+            let spacer_changes = spf
+                .until_intersection(&options)
+                .or(spb.until_intersection(&options));
             self.update_visible();
             self.root
                 .add_event_listener_with_callback(
@@ -160,9 +164,9 @@ impl<'c, Fut: Future + 'c, Renderer: FnMut(usize) -> Fut> VirtualizedList<'c, Fu
                     self.wake_closure.as_ref().unchecked_ref(),
                 )
                 .unwrap_throw();
-            let mut uc = self.signal.until_change();
+            let mut changes = self.signal.until_change().or(spacer_changes.map(|_| ()));
             loop {
-                uc.next().await;
+                changes.next().await;
                 self.update_visible();
             }
         }))
@@ -228,28 +232,3 @@ impl<'c, Fut: Future + 'c, Renderer: FnMut(usize) -> Fut> VirtualizedList<'c, Fu
         state.range = new_start..new_end;
     }
 }
-
-struct Observer {
-    observer: IntersectionObserver,
-}
-
-impl Observer {
-    fn new(root: &Element, spacers: &[&HtmlElement], wake: &js_sys::Function) -> Self {
-        let observer = IntersectionObserver::new_with_options(
-            wake,
-            IntersectionObserverInit::new()
-                .root(Some(root))
-                .root_margin("100%"),
-        )
-        .unwrap_throw();
-        spacers.iter().for_each(|sp| observer.observe(sp));
-
-        Self { observer }
-    }
-}
-
-impl Drop for Observer {
-    fn drop(&mut self) {
-        self.observer.disconnect();
-    }
-}
@@ -0,0 +1,377 @@
+/*!
+Async control of `<audio>` and `<video>` elements.
+
+The [ShortcutMedia] trait adds media-specific methods to
+[Audio][crate::html::Audio], [Video][crate::html::Video],
+and any other [HtmlMediaElement].
+
+```
+# use async_ui_web::{html::{Button, Video}, join, media::PlayError, prelude_traits::*, NoChild};
+# use futures_lite::StreamExt;
+# let _ = async {
+let video = Video::new();
+video.set_src("/intro.webm");
+let play_button = Button::new();
+join((
+    video.render(NoChild),
+    play_button.render("Play".render()),
+    async {
+        if let Err(PlayError::NotAllowed) = video.play_async().await {
+            // autoplay was blocked; wait for the user to ask
+            play_button.until_click().await;
+            let _ = video.play_async().await;
+        }
+    },
+    async {
+        let mut states = video.until_playback_change();
+        while let Some(state) = states.next().await {
+            // update a progress bar with state.current_time / state.duration...
+        }
+    },
+))
+.await;
+# };
+```
+
+[Playlist] plays a list of sources one after another.
+*/
+
+use std::{
+    cell::{Cell, RefCell},
+    fmt::Display,
+    future::Future,
+    ops::Range,
+};
+
+use async_ui_web_html::events::{EmitEvent, EventFutureStream};
+use futures_lite::{stream, Stream, StreamExt};
+use wasm_bindgen::{JsCast, JsValue, UnwrapThrowExt};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{DomException, HtmlMediaElement};
+
+use crate::{race, ReactiveCell};
+
+/// Events after which the [PlaybackState] may have changed.
+const PLAYBACK_EVENTS: &[&str] = &[
+    "play",
+    "pause",
+    "playing",
+    "ended",
+    "timeupdate",
+    "durationchange",
+    "loadedmetadata",
+    "progress",
+    "seeked",
+    "emptied",
+];
+
+/// Media-specific methods for `<audio>` and `<video>` elements.
+///
+/// This is implemented for everything that is an [HtmlMediaElement],
+/// and is included in the [prelude_traits][crate::prelude_traits].
+pub trait ShortcutMedia: AsRef<HtmlMediaElement> {
+    /// Start playing, and wait until playback has actually started.
+    ///
+    /// Browsers often block playback that isn't started by the user (autoplay).
+    /// In that case, this returns [PlayError::NotAllowed].
+    ///
+    /// This isn't called `play` so that it doesn't clash with
+    /// [HtmlMediaElement::play], which returns a JS `Promise`.
+    fn play_async(&self) -> impl Future<Output = Result<(), PlayError>> + 'static {
+        let promise = self.as_ref().play();
+        async move {
+            let promise = promise.map_err(PlayError::from_js)?;
+            JsFuture::from(promise)
+                .await
+                .map(|_| ())
+                .map_err(PlayError::from_js)
+        }
+    }
+    /// Get the current playback state.
+    fn playback_state(&self) -> PlaybackState {
+        PlaybackState::of(self.as_ref())
+    }
+    /// Get a Stream that yields the current [PlaybackState],
+    /// and then the new state every time it changes.
+    ///
+    /// While playing, the current time is updated a few times per second.
+    fn until_playback_change(&self) -> impl Stream<Item = PlaybackState> + 'static {
+        let media = self.as_ref().clone();
+        let mut events = PLAYBACK_EVENTS
+            .iter()
+            .map(|&name| media.until_event::<web_sys::Event>(name.into()))
+            .collect::<Vec<_>>();
+        let changes = stream::poll_fn(move |cx| {
+            // poll every stream, so that all of them have the waker
+            let mut fired = false;
+            for event in events.iter_mut() {
+                fired |= event.poll_next(cx).is_ready();
+            }
+            if fired {
+                std::task::Poll::Ready(Some(()))
+            } else {
+                std::task::Poll::Pending
+            }
+        });
+        let mut last = None;
+        stream::once(()).chain(changes).filter_map(move |_| {
+            let state = PlaybackState::of(&media);
+            (last.as_ref() != Some(&state)).then(|| {
+                last = Some(state.clone());
+                state
+            })
+        })
+    }
+    /// Like [until_event][EmitEvent::until_event] for the `ended` event,
+    /// fired when playback reaches the end of the media.
+    fn until_ended(&self) -> EventFutureStream<web_sys::Event> {
+        self.as_ref().until_event("ended".into())
+    }
+}
+
+impl<T: AsRef<HtmlMediaElement>> ShortcutMedia for T {}
+
+/// Why [play_async][ShortcutMedia::play_async] failed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlayError {
+    /// The browser doesn't allow playback without user interaction
+    /// (the `NotAllowedError` DOMException).
+    NotAllowed,
+    /// The source can't be played (the `NotSupportedError` DOMException).
+    NotSupported,
+    /// Playback was interrupted before it started, for example by a call to
+    /// `pause()` or a change of source (the `AbortError` DOMException).
+    Aborted,
+    /// Some other error.
+    Other(JsValue),
+}
+
+impl PlayError {
+    fn from_js(error: JsValue) -> Self {
+        match error.dyn_ref::<DomException>() {
+            Some(exception) => Self::from_name(&exception.name()).unwrap_or(Self::Other(error)),
+            None => Self::Other(error),
+        }
+    }
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "NotAllowedError" => Some(Self::NotAllowed),
+            "NotSupportedError" => Some(Self::NotSupported),
+            "AbortError" => Some(Self::Aborted),
+            _ => None,
+        }
+    }
+}
+
+impl Display for PlayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotAllowed => f.write_str("playback is not allowed without user interaction"),
+            Self::NotSupported => f.write_str("the media source is not supported"),
+            Self::Aborted => f.write_str("playback was aborted"),
+            Self::Other(error) => write!(f, "playback failed: {error:?}"),
+        }
+    }
+}
+
+impl std::error::Error for PlayError {}
+
+/// A snapshot of the state of a media element.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlaybackState {
+    /// Whether playback is paused (this is also true before it starts).
+    pub paused: bool,
+    /// Whether playback has reached the end.
+    pub ended: bool,
+    /// Current position, in seconds.
+    pub current_time: f64,
+    /// Length of the media, in seconds. `None` if not known yet.
+    /// Infinite for live streams.
+    pub duration: Option<f64>,
+    /// The time ranges (in seconds) that have been downloaded.
+    pub buffered: Vec<Range<f64>>,
+}
+
+impl PlaybackState {
+    fn of(media: &HtmlMediaElement) -> Self {
+        let buffered = media.buffered();
+        let duration = media.duration();
+        Self {
+            paused: media.paused(),
+            ended: media.ended(),
+            current_time: media.current_time(),
+            duration: (!duration.is_nan()).then_some(duration),
+            buffered: (0..buffered.length())
+                .map(|i| buffered.start(i).unwrap_throw()..buffered.end(i).unwrap_throw())
+                .collect(),
+        }
+    }
+}
+
+/**
+Plays a list of sources on a media element, one after another.
+
+```
+# use async_ui_web::{html::Audio, join, media::Playlist, prelude_traits::*, NoChild};
+# let _ = async {
+let audio = Audio::new();
+audio.set_controls(true);
+let playlist = Playlist::new(&audio, ["/track1.mp3", "/track2.mp3", "/track3.mp3"]);
+join((audio.render(NoChild), playlist.run())).await;
+# };
+```
+
+The playlist only does something while [run][Playlist::run] is being awaited.
+*/
+pub struct Playlist {
+    media: HtmlMediaElement,
+    sources: RefCell<Vec<String>>,
+    index: ReactiveCell<usize>,
+    looping: Cell<bool>,
+    autoplay: Cell<bool>,
+}
+
+impl Playlist {
+    /// Create a playlist for the given media element.
+    pub fn new(
+        media: &impl AsRef<HtmlMediaElement>,
+        sources: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            media: media.as_ref().clone(),
+            sources: RefCell::new(sources.into_iter().map(Into::into).collect()),
+            index: ReactiveCell::new(0),
+            looping: Cell::new(false),
+            autoplay: Cell::new(false),
+        }
+    }
+    /// Whether to go back to the first source after the last one ends.
+    /// The default is false.
+    pub fn set_looping(&self, looping: bool) {
+        self.looping.set(looping);
+    }
+    /// Whether to start playing the first source right away.
+    /// The default is false; the user starts playback with the media controls.
+    ///
+    /// Later sources always start playing when the previous one ends.
+    pub fn set_autoplay(&self, autoplay: bool) {
+        self.autoplay.set(autoplay);
+    }
+    /// Replace the list of sources, and go back to the first one.
+    pub fn set_sources(&self, sources: impl IntoIterator<Item = impl Into<String>>) {
+        *self.sources.borrow_mut() = sources.into_iter().map(Into::into).collect();
+        *self.index.borrow_mut() = 0;
+    }
+    /// The position of the current source in the list.
+    ///
+    /// Use [until_change][ReactiveCell::until_change] on this to show
+    /// the current track in your UI.
+    pub fn index(&self) -> &ReactiveCell<usize> {
+        &self.index
+    }
+    /// The current source.
+    pub fn current(&self) -> Option<String> {
+        self.sources.borrow().get(*self.index.borrow()).cloned()
+    }
+    /// Switch to the source at the given position.
+    pub fn jump_to(&self, index: usize) {
+        *self.index.borrow_mut() = index;
+    }
+    /// Switch to the next source. Returns false if there is none.
+    pub fn next(&self) -> bool {
+        let len = self.sources.borrow().len();
+        let next = next_index(*self.index.borrow(), len, self.looping.get());
+        if let Some(next) = next {
+            self.jump_to(next);
+        }
+        next.is_some()
+    }
+    /// Switch to the previous source. Returns false if there is none.
+    pub fn previous(&self) -> bool {
+        let index = *self.index.borrow();
+        let len = self.sources.borrow().len();
+        let previous = match index {
+            0 if self.looping.get() && len > 0 => Some(len - 1),
+            0 => None,
+            _ => Some(index - 1),
+        };
+        if let Some(previous) = previous {
+            self.jump_to(previous);
+        }
+        previous.is_some()
+    }
+    /// Load the sources into the media element in turn.
+    ///
+    /// This async method never completes.
+    pub async fn run(&self) {
+        let mut index_changes = self.index.until_change();
+        let mut play = self.autoplay.get();
+        loop {
+            let Some(source) = self.current() else {
+                index_changes.next().await;
+                continue;
+            };
+            // if the user switches sources while playing, keep playing
+            play |= !self.media.paused();
+            self.media.set_src(&source);
+            if std::mem::take(&mut play) {
+                // if this is rejected (autoplay blocked), the user can press play
+                let _ = self.media.play_async().await;
+            }
+            let ended = race((
+                async {
+                    self.media.until_ended().await;
+                    true
+                },
+                async {
+                    index_changes.next().await;
+                    false
+                },
+            ))
+            .await;
+            if ended {
+                play = self.next();
+                // don't count our own change as the user's
+                index_changes = self.index.until_change();
+                if !play {
+                    // at the end of the list; wait for the user to pick a source
+                    index_changes.next().await;
+                }
+            }
+        }
+    }
+}
+
+/// The position after `index` in a list of length `len`.
+fn next_index(index: usize, len: usize, looping: bool) -> Option<usize> {
+    if index + 1 < len {
+        Some(index + 1)
+    } else if looping && len > 0 {
+        Some(0)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn play_error_names() {
+        assert_eq!(
+            PlayError::from_name("NotAllowedError"),
+            Some(PlayError::NotAllowed)
+        );
+        assert_eq!(PlayError::from_name("AbortError"), Some(PlayError::Aborted));
+        assert_eq!(PlayError::from_name("SyntaxError"), None);
+    }
+
+    #[test]
+    fn playlist_order() {
+        assert_eq!(next_index(0, 3, false), Some(1));
+        assert_eq!(next_index(2, 3, false), None);
+        assert_eq!(next_index(2, 3, true), Some(0));
+        assert_eq!(next_index(0, 0, true), None);
+    }
+}
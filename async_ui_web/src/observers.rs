@@ -0,0 +1,503 @@
+/*!
+[ResizeObserver](https://developer.mozilla.org/en-US/docs/Web/API/ResizeObserver),
+[IntersectionObserver](https://developer.mozilla.org/en-US/docs/Web/API/IntersectionObserver),
+and [MutationObserver](https://developer.mozilla.org/en-US/docs/Web/API/MutationObserver)
+as Streams.
+
+The [ObserveElement] trait adds methods to every element that return
+a [Stream] of observer entries for that element.
+
+```
+# use async_ui_web::{html::Div, prelude_traits::*};
+# use futures_lite::StreamExt;
+# let _ = async {
+let panel = Div::new();
+let mut resizes = panel.until_resize();
+while let Some(entry) = resizes.next().await {
+    let width = entry.content_rect().width();
+    // lay out the panel's content for the new width...
+}
+# };
+```
+
+Observer instances are shared: all streams of the same kind and options use
+one JS observer, which observes each element only once.
+An element is unobserved when its last stream is dropped,
+and the observer is disconnected when it has nothing left to observe.
+*/
+
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+    thread::LocalKey,
+};
+
+use futures_lite::Stream;
+use js_sys::{Array, Function};
+use wasm_bindgen::{prelude::Closure, JsCast, JsValue, UnwrapThrowExt};
+use web_sys::{
+    Element, IntersectionObserver, IntersectionObserverEntry, IntersectionObserverInit,
+    MutationObserver, MutationObserverInit, MutationRecord, Node, ResizeObserver,
+    ResizeObserverEntry,
+};
+
+/// Observe changes to elements.
+///
+/// This is implemented for everything that is an [Element],
+/// and is included in the [prelude_traits][crate::prelude_traits].
+///
+/// See the [module-level documentation][self] for more.
+pub trait ObserveElement: AsRef<Element> {
+    /// Get a Stream that yields an entry every time the size of the element changes.
+    ///
+    /// An entry is also yielded once the element is first observed.
+    fn until_resize(&self) -> ResizeStream {
+        ResizeStream(Subscription::new(self.as_ref(), ()))
+    }
+    /// Get a Stream that yields an entry every time the visibility of the
+    /// element (within the viewport or the root element) crosses a threshold.
+    ///
+    /// An entry is also yielded once the element is first observed.
+    fn until_intersection(&self, options: &IntersectionOptions) -> IntersectionStream {
+        IntersectionStream(Subscription::new(self.as_ref(), options.clone()))
+    }
+    /// Get a Stream that yields a record for every change to the element
+    /// (or its descendants, if `subtree` is set) matching the options.
+    ///
+    /// At least one of `child_list`, `attributes`, and `character_data`
+    /// must be set (or implied by the other options).
+    fn until_mutation(&self, options: &MutationOptions) -> MutationStream {
+        MutationStream(Subscription::new(self.as_ref(), options.clone()))
+    }
+}
+
+impl<T: AsRef<Element>> ObserveElement for T {}
+
+/// Options for [until_intersection][ObserveElement::until_intersection].
+///
+/// ```
+/// # use async_ui_web::{html::Div, observers::IntersectionOptions};
+/// # let _ = async {
+/// let scroller = Div::new();
+/// let root: &web_sys::Element = scroller.as_ref();
+/// let options = IntersectionOptions {
+///     root: Some(root.clone()),
+///     root_margin: "100px".into(),
+///     thresholds: vec![0.0, 0.5, 1.0],
+/// };
+/// # };
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IntersectionOptions {
+    /// The element whose bounds are used for checking visibility.
+    /// `None` means the viewport.
+    pub root: Option<Element>,
+    /// Margin around the root, in CSS `margin` syntax (such as `"10px 20%"`).
+    /// Empty means no margin.
+    pub root_margin: String,
+    /// The visible ratios at which to yield an entry.
+    /// Empty means `[0.0]`.
+    pub thresholds: Vec<f64>,
+}
+
+/// Options for [until_mutation][ObserveElement::until_mutation].
+///
+/// ```
+/// # use async_ui_web::observers::MutationOptions;
+/// let options = MutationOptions {
+///     attributes: true,
+///     attribute_filter: Some(vec!["class".into()]),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MutationOptions {
+    /// Observe children being added or removed.
+    pub child_list: bool,
+    /// Observe attribute changes.
+    pub attributes: bool,
+    /// Observe changes to the text of text nodes.
+    pub character_data: bool,
+    /// Observe descendants as well.
+    pub subtree: bool,
+    /// Record the old values of changed attributes.
+    /// This implies `attributes`.
+    pub attribute_old_value: bool,
+    /// Record the old text of changed text nodes.
+    /// This implies `character_data`.
+    pub character_data_old_value: bool,
+    /// Only observe these attributes.
+    /// This implies `attributes`.
+    pub attribute_filter: Option<Vec<String>>,
+}
+
+/// The kinds of observer, for sharing the registry code.
+trait Kind: Sized + 'static {
+    type Options: PartialEq + Clone;
+    type Observer;
+    type Entry: JsCast + Clone;
+    fn registry() -> &'static LocalKey<RefCell<Registry<Self>>>;
+    fn create(callback: &Function, options: &Self::Options) -> Self::Observer;
+    fn observe(observer: &Self::Observer, target: &Element, options: &Self::Options);
+    /// Stop observing `target`.
+    /// Returns entries that were pending, to be delivered to the other targets.
+    fn unobserve(
+        observer: &Self::Observer,
+        target: &Element,
+        remaining: &[Target<Self>],
+        options: &Self::Options,
+    ) -> Option<Array>;
+    fn disconnect(observer: &Self::Observer);
+    fn matches(options: &Self::Options, target: &Element, entry: &Self::Entry) -> bool;
+}
+
+struct Registry<K: Kind> {
+    observers: Vec<SharedObserver<K>>,
+    next_id: u64,
+}
+
+impl<K: Kind> Default for Registry<K> {
+    fn default() -> Self {
+        Self {
+            observers: Vec::new(),
+            next_id: 0,
+        }
+    }
+}
+
+struct SharedObserver<K: Kind> {
+    id: u64,
+    options: K::Options,
+    observer: K::Observer,
+    targets: Vec<Target<K>>,
+    _callback: Closure<dyn Fn(Array)>,
+}
+
+struct Target<K: Kind> {
+    element: Element,
+    subscribers: Vec<Rc<RefCell<Queue<K::Entry>>>>,
+}
+
+struct Queue<E> {
+    entries: VecDeque<E>,
+    waker: Option<Waker>,
+}
+
+impl<K: Kind> SharedObserver<K> {
+    fn dispatch(&self, entries: Array) {
+        for entry in entries.iter() {
+            let entry: K::Entry = entry.unchecked_into();
+            self.targets
+                .iter()
+                .filter(|target| K::matches(&self.options, &target.element, &entry))
+                .flat_map(|target| target.subscribers.iter())
+                .for_each(|queue| {
+                    let mut queue = queue.borrow_mut();
+                    queue.entries.push_back(entry.clone());
+                    if let Some(waker) = queue.waker.take() {
+                        waker.wake();
+                    }
+                });
+        }
+    }
+}
+
+struct Subscription<K: Kind> {
+    element: Element,
+    options: K::Options,
+    queue: Rc<RefCell<Queue<K::Entry>>>,
+}
+
+impl<K: Kind> Subscription<K> {
+    fn new(element: &Element, options: K::Options) -> Self {
+        let queue = Rc::new(RefCell::new(Queue {
+            entries: VecDeque::new(),
+            waker: None,
+        }));
+        K::registry().with(|registry| {
+            let mut registry = registry.borrow_mut();
+            let index = match registry.observers.iter().position(|o| o.options == options) {
+                Some(index) => index,
+                None => {
+                    let id = registry.next_id;
+                    registry.next_id += 1;
+                    let callback = Closure::<dyn Fn(Array)>::new(move |entries| {
+                        K::registry().with(|registry| {
+                            if let Some(shared) =
+                                registry.borrow().observers.iter().find(|o| o.id == id)
+                            {
+                                shared.dispatch(entries);
+                            }
+                        });
+                        async_ui_web_core::executor::run_now();
+                    });
+                    let observer = K::create(callback.as_ref().unchecked_ref(), &options);
+                    registry.observers.push(SharedObserver {
+                        id,
+                        options: options.clone(),
+                        observer,
+                        targets: Vec::new(),
+                        _callback: callback,
+                    });
+                    registry.observers.len() - 1
+                }
+            };
+            let shared = &mut registry.observers[index];
+            match shared.targets.iter_mut().find(|t| t.element == *element) {
+                Some(target) => target.subscribers.push(queue.clone()),
+                None => {
+                    K::observe(&shared.observer, element, &options);
+                    shared.targets.push(Target {
+                        element: element.clone(),
+                        subscribers: vec![queue.clone()],
+                    });
+                }
+            }
+        });
+        Self {
+            element: element.clone(),
+            options,
+            queue,
+        }
+    }
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<K::Entry>> {
+        let mut queue = self.queue.borrow_mut();
+        match queue.entries.pop_front() {
+            Some(entry) => Poll::Ready(Some(entry)),
+            None => {
+                queue.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<K: Kind> Drop for Subscription<K> {
+    fn drop(&mut self) {
+        K::registry().with(|registry| {
+            let mut registry = registry.borrow_mut();
+            let Some(index) = registry
+                .observers
+                .iter()
+                .position(|o| o.options == self.options)
+            else {
+                return;
+            };
+            let shared = &mut registry.observers[index];
+            let Some(target_index) = shared
+                .targets
+                .iter()
+                .position(|t| t.element == self.element)
+            else {
+                return;
+            };
+            let target = &mut shared.targets[target_index];
+            target.subscribers.retain(|q| !Rc::ptr_eq(q, &self.queue));
+            if !target.subscribers.is_empty() {
+                return;
+            }
+            shared.targets.remove(target_index);
+            if shared.targets.is_empty() {
+                K::disconnect(&shared.observer);
+                registry.observers.remove(index);
+            } else if let Some(pending) = K::unobserve(
+                &shared.observer,
+                &self.element,
+                &shared.targets,
+                &self.options,
+            ) {
+                shared.dispatch(pending);
+            }
+        });
+    }
+}
+
+macro_rules! observer_stream {
+    ($name:ident, $kind:ty, $entry:ty, $doc:literal) => {
+        #[doc = $doc]
+        pub struct $name(Subscription<$kind>);
+
+        impl Stream for $name {
+            type Item = $entry;
+
+            fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+                self.get_mut().0.poll_next(cx)
+            }
+        }
+    };
+}
+
+observer_stream!(
+    ResizeStream,
+    ResizeKind,
+    ResizeObserverEntry,
+    "Stream returned by [until_resize][ObserveElement::until_resize]."
+);
+observer_stream!(
+    IntersectionStream,
+    IntersectionKind,
+    IntersectionObserverEntry,
+    "Stream returned by [until_intersection][ObserveElement::until_intersection]."
+);
+observer_stream!(
+    MutationStream,
+    MutationKind,
+    MutationRecord,
+    "Stream returned by [until_mutation][ObserveElement::until_mutation]."
+);
+
+thread_local! {
+    static RESIZE: RefCell<Registry<ResizeKind>> = RefCell::default();
+    static INTERSECTION: RefCell<Registry<IntersectionKind>> = RefCell::default();
+    static MUTATION: RefCell<Registry<MutationKind>> = RefCell::default();
+}
+
+struct ResizeKind;
+
+impl Kind for ResizeKind {
+    type Options = ();
+    type Observer = ResizeObserver;
+    type Entry = ResizeObserverEntry;
+    fn registry() -> &'static LocalKey<RefCell<Registry<Self>>> {
+        &RESIZE
+    }
+    fn create(callback: &Function, _options: &()) -> ResizeObserver {
+        ResizeObserver::new(callback).unwrap_throw()
+    }
+    fn observe(observer: &ResizeObserver, target: &Element, _options: &()) {
+        observer.observe(target);
+    }
+    fn unobserve(
+        observer: &ResizeObserver,
+        target: &Element,
+        _remaining: &[Target<Self>],
+        _options: &(),
+    ) -> Option<Array> {
+        observer.unobserve(target);
+        None
+    }
+    fn disconnect(observer: &ResizeObserver) {
+        observer.disconnect();
+    }
+    fn matches(_options: &(), target: &Element, entry: &ResizeObserverEntry) -> bool {
+        entry.target() == *target
+    }
+}
+
+struct IntersectionKind;
+
+impl Kind for IntersectionKind {
+    type Options = IntersectionOptions;
+    type Observer = IntersectionObserver;
+    type Entry = IntersectionObserverEntry;
+    fn registry() -> &'static LocalKey<RefCell<Registry<Self>>> {
+        &INTERSECTION
+    }
+    fn create(callback: &Function, options: &IntersectionOptions) -> IntersectionObserver {
+        let init = IntersectionObserverInit::new();
+        init.set_root(options.root.as_ref());
+        if !options.root_margin.is_empty() {
+            init.set_root_margin(&options.root_margin);
+        }
+        if !options.thresholds.is_empty() {
+            let thresholds: Array = options
+                .thresholds
+                .iter()
+                .map(|&t| JsValue::from_f64(t))
+                .collect();
+            init.set_threshold(&thresholds);
+        }
+        IntersectionObserver::new_with_options(callback, &init).unwrap_throw()
+    }
+    fn observe(observer: &IntersectionObserver, target: &Element, _options: &Self::Options) {
+        observer.observe(target);
+    }
+    fn unobserve(
+        observer: &IntersectionObserver,
+        target: &Element,
+        _remaining: &[Target<Self>],
+        _options: &Self::Options,
+    ) -> Option<Array> {
+        observer.unobserve(target);
+        None
+    }
+    fn disconnect(observer: &IntersectionObserver) {
+        observer.disconnect();
+    }
+    fn matches(
+        _options: &Self::Options,
+        target: &Element,
+        entry: &IntersectionObserverEntry,
+    ) -> bool {
+        entry.target() == *target
+    }
+}
+
+struct MutationKind;
+
+impl Kind for MutationKind {
+    type Options = MutationOptions;
+    type Observer = MutationObserver;
+    type Entry = MutationRecord;
+    fn registry() -> &'static LocalKey<RefCell<Registry<Self>>> {
+        &MUTATION
+    }
+    fn create(callback: &Function, _options: &MutationOptions) -> MutationObserver {
+        MutationObserver::new(callback).unwrap_throw()
+    }
+    fn observe(observer: &MutationObserver, target: &Element, options: &MutationOptions) {
+        let init = MutationObserverInit::new();
+        init.set_child_list(options.child_list);
+        init.set_subtree(options.subtree);
+        // `observe` throws if an attribute (or character data) option is
+        // given with `attributes: false` (or `characterData: false`)
+        if options.attributes || options.attribute_old_value || options.attribute_filter.is_some() {
+            init.set_attributes(true);
+        }
+        if options.character_data || options.character_data_old_value {
+            init.set_character_data(true);
+        }
+        if options.attribute_old_value {
+            init.set_attribute_old_value(true);
+        }
+        if options.character_data_old_value {
+            init.set_character_data_old_value(true);
+        }
+        if let Some(filter) = &options.attribute_filter {
+            let filter: Array = filter.iter().map(|a| JsValue::from_str(a)).collect();
+            init.set_attribute_filter(&filter);
+        }
+        observer.observe_with_options(target, &init).unwrap_throw();
+    }
+    fn unobserve(
+        observer: &MutationObserver,
+        _target: &Element,
+        remaining: &[Target<Self>],
+        options: &MutationOptions,
+    ) -> Option<Array> {
+        // MutationObserver can't unobserve a single node;
+        // start over with the remaining ones
+        let pending = observer.take_records();
+        observer.disconnect();
+        remaining
+            .iter()
+            .for_each(|target| Self::observe(observer, &target.element, options));
+        Some(pending)
+    }
+    fn disconnect(observer: &MutationObserver) {
+        observer.disconnect();
+    }
+    fn matches(options: &MutationOptions, target: &Element, record: &MutationRecord) -> bool {
+        let changed = record.target();
+        match changed {
+            Some(changed) if options.subtree => {
+                AsRef::<Node>::as_ref(target).contains(Some(&changed))
+            }
+            Some(changed) => changed == **target,
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,510 @@
+/*!
+Positioning floating elements (like tooltips, popovers, and menus)
+next to an anchor element.
+
+[compute_position] is the engine: pure math on rectangles, working out where
+the floating element goes for a [Placement], flipping it to the other side
+and shifting it along its side to keep it inside the viewport.
+
+[follow_anchor] applies that to real elements, and keeps it up to date as the
+page scrolls or the elements resize. Floating elements are usually rendered
+into the [overlay_root], so that they aren't clipped by the `overflow` of
+their anchor's ancestors.
+[Tooltip][crate::components::Tooltip] and [Popover][crate::components::Popover]
+are built on it.
+
+```
+# use async_ui_web::{html::{Button, Div}, join, positioning::{follow_anchor, Placement, PositionOptions, Side}};
+# let _ = async {
+let button = Button::new();
+let menu = Div::new();
+let options = PositionOptions {
+    placement: Placement::new(Side::Bottom),
+    offset: 4.0,
+    ..Default::default()
+};
+join((follow_anchor(&button, &menu, &options), async {
+    // render the button and the menu...
+}))
+.await;
+# };
+```
+*/
+
+use std::cell::RefCell;
+
+use async_ui_web_core::window::{DOCUMENT, WINDOW};
+use async_ui_web_html::events::EmitEvent;
+use futures_lite::StreamExt;
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::{DomRect, Element, HtmlElement};
+
+use crate::{observers::ObserveElement, race, style::ShortcutStyle};
+
+thread_local! {
+    static OVERLAY_ROOT: RefCell<Option<HtmlElement>> = const { RefCell::new(None) };
+}
+
+/// A rectangle, in CSS pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+    pub fn right(&self) -> f64 {
+        self.x + self.width
+    }
+    pub fn bottom(&self) -> f64 {
+        self.y + self.height
+    }
+}
+
+impl From<DomRect> for Rect {
+    fn from(rect: DomRect) -> Self {
+        Self::new(rect.x(), rect.y(), rect.width(), rect.height())
+    }
+}
+
+/// Which side of the anchor the floating element goes on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Side {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl Side {
+    /// The side across the anchor from this one.
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Top => Self::Bottom,
+            Self::Bottom => Self::Top,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+    fn is_vertical(self) -> bool {
+        matches!(self, Self::Top | Self::Bottom)
+    }
+}
+
+/// How the floating element lines up with the anchor, along the side it is on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Align {
+    /// Line up the left (or top) edges.
+    Start,
+    /// Line up the centers.
+    #[default]
+    Center,
+    /// Line up the right (or bottom) edges.
+    End,
+}
+
+/// Where the floating element goes relative to the anchor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Placement {
+    pub side: Side,
+    pub align: Align,
+}
+
+impl Placement {
+    /// Centered on the given side.
+    pub fn new(side: Side) -> Self {
+        Self {
+            side,
+            align: Align::Center,
+        }
+    }
+    /// The placement as a string like `top`, `bottom-start`, or `left-end`.
+    pub fn as_str(&self) -> &'static str {
+        use {Align::*, Side::*};
+        match (self.side, self.align) {
+            (Top, Start) => "top-start",
+            (Top, Center) => "top",
+            (Top, End) => "top-end",
+            (Bottom, Start) => "bottom-start",
+            (Bottom, Center) => "bottom",
+            (Bottom, End) => "bottom-end",
+            (Left, Start) => "left-start",
+            (Left, Center) => "left",
+            (Left, End) => "left-end",
+            (Right, Start) => "right-start",
+            (Right, Center) => "right",
+            (Right, End) => "right-end",
+        }
+    }
+}
+
+/// Options for [compute_position].
+#[derive(Clone, Debug, PartialEq)]
+pub struct PositionOptions {
+    /// Where to put the floating element, if it fits.
+    pub placement: Placement,
+    /// Gap between the anchor and the floating element.
+    pub offset: f64,
+    /// Move to the opposite side if the floating element doesn't fit
+    /// on the side given by `placement` (and fits better there).
+    pub flip: bool,
+    /// Move the floating element along its side to keep it in the viewport.
+    pub shift: bool,
+    /// How close to the edges of the viewport the floating element may go.
+    pub viewport_padding: f64,
+    /// How close to the corners of the floating element the arrow may go.
+    pub arrow_padding: f64,
+}
+
+impl Default for PositionOptions {
+    fn default() -> Self {
+        Self {
+            placement: Placement::new(Side::Bottom),
+            offset: 0.0,
+            flip: true,
+            shift: true,
+            viewport_padding: 8.0,
+            arrow_padding: 8.0,
+        }
+    }
+}
+
+/// The result of [compute_position].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Position {
+    /// Where to put the left edge of the floating element.
+    pub x: f64,
+    /// Where to put the top edge of the floating element.
+    pub y: f64,
+    /// The placement actually used (the side may be flipped).
+    pub placement: Placement,
+    /// Where the arrow should point from, measured along the edge of the
+    /// floating element that faces the anchor (from its left or top corner).
+    /// This points at the center of the anchor where possible.
+    pub arrow: f64,
+}
+
+/// Work out where to put a floating element of the given size next to the anchor.
+///
+/// All rectangles must be in the same coordinates (usually the viewport's,
+/// as from [getBoundingClientRect](https://developer.mozilla.org/en-US/docs/Web/API/Element/getBoundingClientRect)).
+pub fn compute_position(
+    anchor: Rect,
+    floating: (f64, f64),
+    viewport: Rect,
+    options: &PositionOptions,
+) -> Position {
+    let (width, height) = floating;
+    let padding = options.viewport_padding;
+    let mut placement = options.placement;
+    if options.flip {
+        let overflow = |side| main_axis_overflow(anchor, floating, viewport, side, options);
+        let here = overflow(placement.side);
+        let there = overflow(placement.side.opposite());
+        if here > 0.0 && there < here {
+            placement.side = placement.side.opposite();
+        }
+    }
+    let (mut x, mut y) = place(anchor, floating, placement, options.offset);
+    if options.shift {
+        if placement.side.is_vertical() {
+            x = clamp_within(x, width, viewport.x + padding, viewport.right() - padding);
+        } else {
+            y = clamp_within(y, height, viewport.y + padding, viewport.bottom() - padding);
+        }
+    }
+    let (anchor_center, start, length) = if placement.side.is_vertical() {
+        (anchor.x + anchor.width / 2.0, x, width)
+    } else {
+        (anchor.y + anchor.height / 2.0, y, height)
+    };
+    let arrow = clamp_within(
+        anchor_center - start,
+        0.0,
+        options.arrow_padding.min(length / 2.0),
+        (length - options.arrow_padding).max(length / 2.0),
+    );
+    Position {
+        x,
+        y,
+        placement,
+        arrow,
+    }
+}
+
+/// The top left corner of the floating element for a placement, before any flip or shift.
+fn place(
+    anchor: Rect,
+    (width, height): (f64, f64),
+    placement: Placement,
+    offset: f64,
+) -> (f64, f64) {
+    let cross = |start: f64, anchor_length: f64, length: f64| match placement.align {
+        Align::Start => start,
+        Align::Center => start + (anchor_length - length) / 2.0,
+        Align::End => start + anchor_length - length,
+    };
+    match placement.side {
+        Side::Top => (
+            cross(anchor.x, anchor.width, width),
+            anchor.y - offset - height,
+        ),
+        Side::Bottom => (
+            cross(anchor.x, anchor.width, width),
+            anchor.bottom() + offset,
+        ),
+        Side::Left => (
+            anchor.x - offset - width,
+            cross(anchor.y, anchor.height, height),
+        ),
+        Side::Right => (
+            anchor.right() + offset,
+            cross(anchor.y, anchor.height, height),
+        ),
+    }
+}
+
+/// How far the floating element sticks out of the viewport on the given side.
+fn main_axis_overflow(
+    anchor: Rect,
+    (width, height): (f64, f64),
+    viewport: Rect,
+    side: Side,
+    options: &PositionOptions,
+) -> f64 {
+    let padding = options.viewport_padding;
+    let offset = options.offset;
+    match side {
+        Side::Top => (viewport.y + padding) - (anchor.y - offset - height),
+        Side::Bottom => (anchor.bottom() + offset + height) - (viewport.bottom() - padding),
+        Side::Left => (viewport.x + padding) - (anchor.x - offset - width),
+        Side::Right => (anchor.right() + offset + width) - (viewport.right() - padding),
+    }
+}
+
+/// Move a span of the given length, starting at `start`, to be within `min..max`.
+/// If it doesn't fit, line it up with `min`.
+fn clamp_within(start: f64, length: f64, min: f64, max: f64) -> f64 {
+    start.min(max - length).max(min)
+}
+
+/// The element that floating elements are rendered into.
+///
+/// This is a `<div>` at the end of the `<body>` of the document, created the
+/// first time it is needed. Render into it with
+/// [ContainerNodeFuture::new_root][async_ui_web_core::ContainerNodeFuture::new_root].
+pub fn overlay_root() -> HtmlElement {
+    OVERLAY_ROOT.with(|root| {
+        let mut root = root.borrow_mut();
+        let root = root.get_or_insert_with(|| {
+            let element: HtmlElement =
+                DOCUMENT.with(|doc| doc.create_element("div").unwrap_throw().unchecked_into());
+            element
+                .set_attribute("data-overlay-root", "")
+                .unwrap_throw();
+            element
+        });
+        // the app may have replaced the body
+        if !root.is_connected() {
+            DOCUMENT.with(|doc| doc.body().unwrap_throw().append_child(root).unwrap_throw());
+        }
+        root.clone()
+    })
+}
+
+/// Keep `floating` positioned next to `anchor`.
+///
+/// The floating element is given `position: fixed`, and moved with its `left`
+/// and `top` styles. Its `data-placement` attribute is set to the
+/// [placement][Placement::as_str] used, and its `--arrow-offset` CSS variable
+/// to the [arrow][Position::arrow] position, for styling.
+///
+/// The position is updated when anything scrolls, and when the window,
+/// the anchor, or the floating element resizes.
+///
+/// This async function never completes.
+pub async fn follow_anchor(
+    anchor: &impl AsRef<Element>,
+    floating: &impl AsRef<HtmlElement>,
+    options: &PositionOptions,
+) {
+    let anchor = anchor.as_ref();
+    let floating = floating.as_ref();
+    floating.set_style("position", "fixed");
+    floating.set_style("margin", "0");
+    let mut scrolls = WINDOW.with(|w| w.until_event::<web_sys::Event>("scroll".into()));
+    // scroll events don't bubble, but can be captured
+    scrolls.set_capture(true);
+    scrolls.set_passive(true);
+    let mut resizes = WINDOW.with(|w| w.until_event::<web_sys::Event>("resize".into()));
+    let mut anchor_resizes = anchor.until_resize();
+    let mut floating_resizes = floating.until_resize();
+    loop {
+        update_position(anchor, floating, options);
+        race((
+            async {
+                (&mut scrolls).await;
+            },
+            async {
+                (&mut resizes).await;
+            },
+            async {
+                anchor_resizes.next().await;
+            },
+            async {
+                floating_resizes.next().await;
+            },
+        ))
+        .await;
+    }
+}
+
+fn update_position(anchor: &Element, floating: &HtmlElement, options: &PositionOptions) {
+    let anchor_rect = Rect::from(anchor.get_bounding_client_rect());
+    let floating_rect = floating.get_bounding_client_rect();
+    let viewport = DOCUMENT.with(|doc| {
+        let root = doc.document_element().unwrap_throw();
+        Rect::new(
+            0.0,
+            0.0,
+            root.client_width() as f64,
+            root.client_height() as f64,
+        )
+    });
+    let position = compute_position(
+        anchor_rect,
+        (floating_rect.width(), floating_rect.height()),
+        viewport,
+        options,
+    );
+    floating.set_style("left", format_args!("{}px", position.x));
+    floating.set_style("top", format_args!("{}px", position.y));
+    floating.set_style("--arrow-offset", format_args!("{}px", position.arrow));
+    floating
+        .set_attribute("data-placement", position.placement.as_str())
+        .ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIEWPORT: Rect = Rect {
+        x: 0.0,
+        y: 0.0,
+        width: 800.0,
+        height: 600.0,
+    };
+
+    fn options(side: Side, align: Align) -> PositionOptions {
+        PositionOptions {
+            placement: Placement { side, align },
+            offset: 4.0,
+            viewport_padding: 0.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn basic_placements() {
+        let anchor = Rect::new(100.0, 100.0, 50.0, 20.0);
+        let pos = compute_position(
+            anchor,
+            (30.0, 10.0),
+            VIEWPORT,
+            &options(Side::Bottom, Align::Center),
+        );
+        assert_eq!((pos.x, pos.y), (110.0, 124.0));
+        let pos = compute_position(
+            anchor,
+            (30.0, 10.0),
+            VIEWPORT,
+            &options(Side::Top, Align::Start),
+        );
+        assert_eq!((pos.x, pos.y), (100.0, 86.0));
+        let pos = compute_position(
+            anchor,
+            (30.0, 10.0),
+            VIEWPORT,
+            &options(Side::Right, Align::End),
+        );
+        assert_eq!((pos.x, pos.y), (154.0, 110.0));
+        let pos = compute_position(
+            anchor,
+            (30.0, 10.0),
+            VIEWPORT,
+            &options(Side::Left, Align::Center),
+        );
+        assert_eq!((pos.x, pos.y), (66.0, 105.0));
+        assert_eq!(pos.placement.as_str(), "left");
+    }
+
+    #[test]
+    fn flips_when_out_of_room() {
+        // near the top; no room above
+        let anchor = Rect::new(100.0, 5.0, 50.0, 20.0);
+        let pos = compute_position(
+            anchor,
+            (30.0, 40.0),
+            VIEWPORT,
+            &options(Side::Top, Align::Center),
+        );
+        assert_eq!(pos.placement.side, Side::Bottom);
+        assert_eq!(pos.y, 29.0);
+
+        let mut no_flip = options(Side::Top, Align::Center);
+        no_flip.flip = false;
+        let pos = compute_position(anchor, (30.0, 40.0), VIEWPORT, &no_flip);
+        assert_eq!(pos.placement.side, Side::Top);
+        assert_eq!(pos.y, -39.0);
+    }
+
+    #[test]
+    fn no_flip_when_other_side_is_worse() {
+        // a tall element that fits neither side; the top has more room
+        let anchor = Rect::new(100.0, 400.0, 50.0, 20.0);
+        let pos = compute_position(
+            anchor,
+            (30.0, 500.0),
+            VIEWPORT,
+            &options(Side::Top, Align::Center),
+        );
+        assert_eq!(pos.placement.side, Side::Top);
+    }
+
+    #[test]
+    fn shifts_and_arrow() {
+        // near the right edge
+        let anchor = Rect::new(770.0, 100.0, 20.0, 20.0);
+        let pos = compute_position(
+            anchor,
+            (100.0, 30.0),
+            VIEWPORT,
+            &options(Side::Bottom, Align::Center),
+        );
+        assert_eq!(pos.x, 700.0);
+        // the arrow still points at the center of the anchor
+        assert_eq!(pos.arrow, 80.0);
+
+        // anchor off the left edge: the arrow stays away from the corner
+        let anchor = Rect::new(-50.0, 100.0, 20.0, 20.0);
+        let pos = compute_position(
+            anchor,
+            (100.0, 30.0),
+            VIEWPORT,
+            &options(Side::Bottom, Align::Center),
+        );
+        assert_eq!(pos.x, 0.0);
+        assert_eq!(pos.arrow, 8.0);
+    }
+}
@@ -0,0 +1,136 @@
+/*!
+A common interface over state that can be observed for changes.
+
+Many utilities in this crate (such as the [form bindings][crate::bind])
+work with any state that implements [Reactive] or [ReactiveMut].
+They are implemented for [ReactiveCell], and, with the `x-bow` feature enabled,
+for x-bow paths wrapped in `XBowPath`.
+*/
+
+use std::rc::Rc;
+
+use async_ui_internal_utils::reactive_cell::UntilChangeFuture;
+use futures_lite::Stream;
+
+use crate::ReactiveCell;
+
+/// State that can be read and observed for changes.
+pub trait Reactive {
+    /// The type of the value held.
+    type Value;
+    /// The type returned by [until_value_change][Reactive::until_value_change].
+    type UntilChange<'a>: Stream<Item = ()> + Unpin + 'a
+    where
+        Self: 'a;
+    /// Call the given function with a reference to the current value.
+    ///
+    /// Returns `None` if the value is not available
+    /// (for example, if an x-bow path points to a nonexistent enum variant).
+    fn with_value<R>(&self, f: impl FnOnce(&Self::Value) -> R) -> Option<R>;
+    /// Get a [Stream] that fires every time the value changes.
+    ///
+    /// Like [ReactiveCell::until_change], the stream does not fire on
+    /// its first poll; it only fires for changes after that.
+    fn until_value_change(&self) -> Self::UntilChange<'_>;
+}
+
+/// State that can be read, observed, and written to.
+pub trait ReactiveMut: Reactive {
+    /// Replace the value, notifying all observers.
+    ///
+    /// If the value is not available, the new value may be dropped.
+    fn set_value(&self, value: Self::Value);
+}
+
+impl<T> Reactive for ReactiveCell<T> {
+    type Value = T;
+    type UntilChange<'a>
+        = UntilChangeFuture<'a, T>
+    where
+        Self: 'a;
+    fn with_value<R>(&self, f: impl FnOnce(&Self::Value) -> R) -> Option<R> {
+        Some(f(&self.borrow()))
+    }
+    fn until_value_change(&self) -> Self::UntilChange<'_> {
+        self.until_change()
+    }
+}
+
+impl<T> ReactiveMut for ReactiveCell<T> {
+    fn set_value(&self, value: Self::Value) {
+        *self.borrow_mut() = value;
+    }
+}
+
+macro_rules! impl_through_pointer {
+    ($ptr:ty) => {
+        impl<S: Reactive + ?Sized> Reactive for $ptr {
+            type Value = S::Value;
+            type UntilChange<'a>
+                = S::UntilChange<'a>
+            where
+                Self: 'a;
+            fn with_value<R>(&self, f: impl FnOnce(&Self::Value) -> R) -> Option<R> {
+                S::with_value(self, f)
+            }
+            fn until_value_change(&self) -> Self::UntilChange<'_> {
+                S::until_value_change(self)
+            }
+        }
+        impl<S: ReactiveMut + ?Sized> ReactiveMut for $ptr {
+            fn set_value(&self, value: Self::Value) {
+                S::set_value(self, value)
+            }
+        }
+    };
+}
+impl_through_pointer!(&S);
+impl_through_pointer!(Rc<S>);
+
+#[cfg(feature = "x-bow")]
+pub use self::x_bow_impl::XBowPath;
+
+#[cfg(feature = "x-bow")]
+mod x_bow_impl {
+    use x_bow::{Path, PathExt};
+
+    use super::{Reactive, ReactiveMut};
+
+    /// Wrapper to use an [x-bow](x_bow) path as [Reactive] state.
+    ///
+    /// ```
+    /// # use async_ui_web::reactive::XBowPath;
+    /// # #[derive(x_bow::Trackable)]
+    /// # struct State { name: String }
+    /// # let store = x_bow::Store::new(State { name: String::new() });
+    /// let name = XBowPath(store.build_path().name());
+    /// ```
+    #[derive(Clone, Copy, Debug)]
+    pub struct XBowPath<P>(pub P);
+
+    impl<P: Path> Reactive for XBowPath<P>
+    where
+        P::Out: Sized,
+    {
+        type Value = P::Out;
+        type UntilChange<'a>
+            = x_bow::path_ext_wrappers::UntilChange<'a>
+        where
+            Self: 'a;
+        fn with_value<R>(&self, f: impl FnOnce(&Self::Value) -> R) -> Option<R> {
+            self.0.borrow_opt().map(|value| f(&value))
+        }
+        fn until_value_change(&self) -> Self::UntilChange<'_> {
+            self.0.until_change()
+        }
+    }
+
+    impl<P: Path> ReactiveMut for XBowPath<P>
+    where
+        P::Out: Sized,
+    {
+        fn set_value(&self, value: Self::Value) {
+            let _ = self.0.set_opt(value);
+        }
+    }
+}
@@ -0,0 +1,638 @@
+//! Client-side routing.
+//!
+//! A [Router] renders a different future depending on the URL of the page.
+//! The future of a route is dropped when the user navigates away from it.
+//!
+//! ```
+//! # use async_ui_web::{html::Div, join, prelude_traits::*, router::{HistoryMode, Link, Route, Router, Routes}};
+//! # async fn home() {}
+//! # async fn user_page(id: u64) {}
+//! # async fn settings_menu() {}
+//! # async fn profile_settings() {}
+//! # let _ = async {
+//! let routes = Routes::new()
+//!     .route(Route::new("/", |_| home()))
+//!     .route(Route::new("/users/:id", |ctx| async move {
+//!         match ctx.params().get("id") {
+//!             Some(id) => user_page(id).await,
+//!             None => "No such user".render().await,
+//!         }
+//!     }))
+//!     .route(
+//!         Route::new("/settings", |ctx| async move {
+//!             // the matching child route renders into the outlet
+//!             join((settings_menu(), ctx.outlet())).await;
+//!         })
+//!         .children(Routes::new().route(Route::new("/profile", |_| profile_settings()))),
+//!     )
+//!     .fallback(|_| "Page not found".render());
+//! let router = Router::new(HistoryMode::History, routes);
+//! let home_link = Link::new("/");
+//! join((
+//!     Div::new().render(home_link.render("Home".render())),
+//!     router.render(),
+//! ))
+//! .await;
+//! # };
+//! ```
+//!
+//! ### Patterns
+//! *   A plain segment (`users`) matches itself.
+//! *   A segment starting with `:` (`:id`) matches any one segment and captures
+//!     it as a parameter. Get it, parsed into any [FromStr][std::str::FromStr]
+//!     type, with [Params::get].
+//! *   A segment starting with `*` (`*rest`) matches all the remaining segments.
+//!     It must be last.
+//!
+//! Routes are tried in the order they were added.
+//! A route with [children][Route::children] matches the start of the path, and
+//! its children match the rest. Its [outlet][RouteContext::outlet] renders the
+//! matching child route.
+//!
+//! ### Navigation
+//! [Link] renders an `<a>` that navigates without reloading the page.
+//! To navigate from code, use [navigate] or [navigate_replace].
+//! Paths given to these should start with `/`.
+//!
+//! ### Query parameters
+//! [bind_query] keeps a piece of state in sync with a query parameter,
+//! so that it survives reloads and can be shared in links.
+//!
+//! ### Guards
+//! A route may have an async [guard][Route::guard] that runs before the route
+//! is shown, and decides whether to [allow][GuardOutcome::Allow] it,
+//! [block][GuardOutcome::Block] it (staying on the current page), or
+//! [redirect][GuardOutcome::Redirect] somewhere else.
+//!
+//! ### Modes
+//! In [HistoryMode::History], paths are in the URL like `/users/42`. The server
+//! must serve the app for all such URLs. In [HistoryMode::Hash], paths are in
+//! the fragment, like `/#/users/42`, which works with any static file server.
+
+mod pattern;
+mod query;
+
+use std::{
+    cell::{Cell, RefCell},
+    future::{pending, Future},
+    ops::Deref,
+    pin::Pin,
+    rc::Rc,
+};
+
+use async_ui_web_core::window::WINDOW;
+use async_ui_web_html::{
+    events::{EmitElementEvent, EmitEvent},
+    nodes::Anchor,
+};
+use futures_lite::StreamExt;
+use wasm_bindgen::{JsValue, UnwrapThrowExt};
+
+use crate::{join, race, ReactiveCell};
+
+pub use pattern::Params;
+use pattern::{split_path, Pattern};
+pub use query::{bind_query, query_param, QueryHistory};
+
+type Handler = Rc<dyn Fn(RouteContext) -> Pin<Box<dyn Future<Output = ()>>>>;
+type Guard = Rc<dyn Fn(Params) -> Pin<Box<dyn Future<Output = GuardOutcome>>>>;
+
+/// How many redirects in a row are followed before giving up.
+const MAX_REDIRECTS: usize = 8;
+
+thread_local! {
+    static MODE: Cell<HistoryMode> = const { Cell::new(HistoryMode::History) };
+    /// The current path (with the query string), as last read from or written to the URL.
+    static LOCATION: Rc<ReactiveCell<String>> = Rc::new(ReactiveCell::new(read_location(HistoryMode::History)));
+}
+
+/// Where in the URL the path is kept.
+///
+/// See the [module-level documentation][self].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistoryMode {
+    /// Use the path of the URL, with the
+    /// [History API](https://developer.mozilla.org/en-US/docs/Web/API/History_API).
+    History,
+    /// Use the fragment of the URL (the part after `#`).
+    Hash,
+}
+
+/// What a [route guard][Route::guard] decided.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GuardOutcome {
+    /// Show the route.
+    Allow,
+    /// Stay on the current route, and put its path back in the URL.
+    ///
+    /// If no route has been shown yet (such as when the page is first loaded),
+    /// nothing is shown until the location changes.
+    Block,
+    /// Navigate to the given path instead.
+    ///
+    /// A redirect to the same path, or more than a few redirects in a row,
+    /// is treated as [Block][GuardOutcome::Block].
+    Redirect(String),
+}
+
+/// A route: a pattern, and what to render when it matches.
+pub struct Route {
+    pattern: Pattern,
+    handler: Handler,
+    guard: Option<Guard>,
+    children: Option<Routes>,
+}
+
+impl Route {
+    /// Create a route that renders the future returned by `handler`
+    /// when the path matches `pattern`.
+    pub fn new<F: Future<Output = ()> + 'static>(
+        pattern: &str,
+        handler: impl Fn(RouteContext) -> F + 'static,
+    ) -> Self {
+        Self {
+            pattern: Pattern::parse(pattern),
+            handler: Rc::new(move |ctx| Box::pin(handler(ctx))),
+            guard: None,
+            children: None,
+        }
+    }
+    /// Run the given async function before showing this route.
+    ///
+    /// The guard is given the parameters of the route. It runs every time the
+    /// path changes to one that matches this route (even if the route was
+    /// already shown with different parameters).
+    pub fn guard<F: Future<Output = GuardOutcome> + 'static>(
+        mut self,
+        guard: impl Fn(Params) -> F + 'static,
+    ) -> Self {
+        self.guard = Some(Rc::new(move |params| Box::pin(guard(params))));
+        self
+    }
+    /// Nest routes inside this one.
+    ///
+    /// This route then matches the start of the path, and the child routes
+    /// match the rest. The matching child is rendered by
+    /// [RouteContext::outlet].
+    pub fn children(mut self, routes: Routes) -> Self {
+        self.children = Some(routes);
+        self
+    }
+}
+
+/// A list of [Route]s, tried in order.
+#[derive(Default)]
+pub struct Routes {
+    routes: Vec<Route>,
+    fallback: Option<Handler>,
+}
+
+impl Routes {
+    /// Create an empty list of routes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add a route.
+    pub fn route(mut self, route: Route) -> Self {
+        self.routes.push(route);
+        self
+    }
+    /// Set what to render when none of the routes match (a "not found" page).
+    pub fn fallback<F: Future<Output = ()> + 'static>(
+        mut self,
+        handler: impl Fn(RouteContext) -> F + 'static,
+    ) -> Self {
+        self.fallback = Some(Rc::new(move |ctx| Box::pin(handler(ctx))));
+        self
+    }
+    fn is_fallback(&self, handler: &Handler) -> bool {
+        self.fallback
+            .as_ref()
+            .is_some_and(|fallback| Rc::ptr_eq(fallback, handler))
+    }
+    /// Find the chain of routes (outermost first) that matches the path.
+    fn match_path<'r>(&'r self, path: &[String], params: &Params) -> Option<Vec<Matched<'r>>> {
+        for route in self.routes.iter() {
+            let mut params = params.clone();
+            let exact = route.children.is_none();
+            let Some(consumed) = route.pattern.match_segments(path, exact, &mut params) else {
+                continue;
+            };
+            let rest = &path[consumed..];
+            let mut chain = vec![Matched {
+                handler: &route.handler,
+                guard: route.guard.as_ref(),
+                params: params.clone(),
+            }];
+            if let Some(children) = &route.children {
+                match children.match_path(rest, &params) {
+                    // with nothing left, the parent route matches on its own
+                    // (rather than with the "not found" page of its children)
+                    Some(inner) if rest.is_empty() && children.is_fallback(inner[0].handler) => {}
+                    Some(inner) => chain.extend(inner),
+                    None if rest.is_empty() => {}
+                    None => continue,
+                }
+            }
+            return Some(chain);
+        }
+        self.fallback.as_ref().map(|handler| {
+            vec![Matched {
+                handler,
+                guard: None,
+                params: params.clone(),
+            }]
+        })
+    }
+}
+
+struct Matched<'r> {
+    handler: &'r Handler,
+    guard: Option<&'r Guard>,
+    params: Params,
+}
+
+/// What is rendered at one level of nesting.
+#[derive(Clone)]
+struct Level {
+    handler: Handler,
+    params: Params,
+}
+
+impl PartialEq for Level {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.handler, &other.handler) && self.params == other.params
+    }
+}
+
+struct Shared {
+    levels: ReactiveCell<Rc<[Level]>>,
+}
+
+/// Given to the handler of a [Route] when it is rendered.
+pub struct RouteContext {
+    shared: Rc<Shared>,
+    depth: usize,
+    params: Params,
+}
+
+impl RouteContext {
+    /// The parameters captured from the path, including those of parent routes.
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+    /// Render the child route that matches the rest of the path.
+    ///
+    /// This renders nothing if no child route matches,
+    /// or if the route has no [children][Route::children].
+    ///
+    /// This async method never completes.
+    pub fn outlet(&self) -> impl Future<Output = ()> + 'static {
+        render_level(self.shared.clone(), self.depth + 1)
+    }
+}
+
+/// Renders the route that matches the current path.
+///
+/// See the [module-level documentation][self] for more.
+pub struct Router {
+    routes: Routes,
+    shared: Rc<Shared>,
+}
+
+impl Router {
+    /// Create a router with the given routes.
+    ///
+    /// The mode is used by all of [Link], [navigate], and [navigate_replace].
+    /// An app should only use one mode.
+    pub fn new(mode: HistoryMode, routes: Routes) -> Self {
+        MODE.with(|m| m.set(mode));
+        sync_location();
+        Self {
+            routes,
+            shared: Rc::new(Shared {
+                levels: ReactiveCell::new(Rc::from([])),
+            }),
+        }
+    }
+    /// Render the routes.
+    ///
+    /// This async method never completes.
+    pub async fn render(&self) {
+        join((render_level(self.shared.clone(), 0), self.follow_location())).await;
+    }
+    async fn follow_location(&self) {
+        let location = LOCATION.with(Rc::clone);
+        let mut popstates = WINDOW.with(|w| w.until_event::<web_sys::Event>("popstate".into()));
+        let mut hashchanges = WINDOW.with(|w| w.until_event::<web_sys::Event>("hashchange".into()));
+        // the last path that was allowed, and whose routes are shown
+        let mut shown: Option<String> = None;
+        // the last path that was resolved; it isn't resolved again until the location changes
+        let mut settled: Option<String> = None;
+        let mut redirects = 0;
+        loop {
+            let path = location.borrow().clone();
+            // changes to only the query string don't affect routes
+            if settled.as_deref().map(without_query) != Some(without_query(&path)) {
+                let outcome = match self.resolve(&path).await {
+                    GuardOutcome::Redirect(to)
+                        if redirects >= MAX_REDIRECTS
+                            || without_query(&to) == without_query(&path) =>
+                    {
+                        // a redirect loop; stay put
+                        GuardOutcome::Block
+                    }
+                    outcome => outcome,
+                };
+                match outcome {
+                    GuardOutcome::Allow => {
+                        shown = Some(path.clone());
+                        settled = Some(path);
+                    }
+                    GuardOutcome::Block => match &shown {
+                        Some(shown) => {
+                            navigate_replace(shown);
+                            settled = Some(shown.clone());
+                        }
+                        // nothing to go back to; wait for the location to change
+                        None => settled = Some(path),
+                    },
+                    GuardOutcome::Redirect(to) => {
+                        redirects += 1;
+                        navigate_replace(&to);
+                    }
+                }
+                // the location may have changed while guards were running
+                continue;
+            }
+            redirects = 0;
+            let mut changes = location.until_change();
+            race((
+                async {
+                    changes.next().await;
+                },
+                async {
+                    (&mut popstates).await;
+                },
+                async {
+                    (&mut hashchanges).await;
+                },
+            ))
+            .await;
+            sync_location();
+        }
+    }
+    /// Match the path, run the guards, and show the routes if allowed.
+    async fn resolve(&self, path: &str) -> GuardOutcome {
+        let segments = path_segments(path);
+        let chain = self
+            .routes
+            .match_path(&segments, &Params::default())
+            .unwrap_or_default();
+        for matched in chain.iter() {
+            if let Some(guard) = matched.guard {
+                let outcome = guard(matched.params.clone()).await;
+                if outcome != GuardOutcome::Allow {
+                    return outcome;
+                }
+            }
+        }
+        let levels: Rc<[Level]> = chain
+            .into_iter()
+            .map(|matched| Level {
+                handler: matched.handler.clone(),
+                params: matched.params,
+            })
+            .collect();
+        *self.shared.levels.borrow_mut() = levels;
+        GuardOutcome::Allow
+    }
+}
+
+/// Render the route at the given level of nesting, switching it when it changes.
+async fn render_level(shared: Rc<Shared>, depth: usize) {
+    loop {
+        let current = shared.levels.borrow().get(depth).cloned();
+        let mut changes = shared.levels.until_change();
+        let changed = async {
+            while changes.next().await.is_some() {
+                if shared.levels.borrow().get(depth) != current.as_ref() {
+                    break;
+                }
+            }
+        };
+        match current.clone() {
+            Some(level) => {
+                let ctx = RouteContext {
+                    shared: shared.clone(),
+                    depth,
+                    params: level.params.clone(),
+                };
+                race((
+                    async {
+                        (level.handler)(ctx).await;
+                        pending::<()>().await;
+                    },
+                    changed,
+                ))
+                .await;
+            }
+            None => changed.await,
+        }
+    }
+}
+
+/// Split a path (without the query string or fragment) into decoded segments.
+fn path_segments(path: &str) -> Vec<String> {
+    split_path(without_query(path))
+        .map(|segment| {
+            js_sys::decode_uri_component(segment)
+                .map(String::from)
+                .unwrap_or_else(|_| segment.to_owned())
+        })
+        .collect()
+}
+
+fn without_query(path: &str) -> &str {
+    path.split(['?', '#']).next().unwrap_or_default()
+}
+
+/// Read the path (with the query string) from the URL.
+fn read_location(mode: HistoryMode) -> String {
+    WINDOW.with(|window| {
+        let location = window.location();
+        match mode {
+            HistoryMode::History => {
+                location.pathname().unwrap_throw() + &location.search().unwrap_throw()
+            }
+            HistoryMode::Hash => {
+                let hash = location.hash().unwrap_throw();
+                match hash.strip_prefix('#') {
+                    Some(path) if !path.is_empty() => path.to_owned(),
+                    _ => "/".to_owned(),
+                }
+            }
+        }
+    })
+}
+
+/// Update our copy of the location from the URL, if it changed.
+fn sync_location() {
+    set_location(read_location(MODE.with(Cell::get)));
+}
+
+fn set_location(path: String) {
+    LOCATION.with(|location| {
+        if *location.borrow() != path {
+            *location.borrow_mut() = path;
+        }
+    });
+}
+
+/// Turn a path into a URL for an `href` attribute.
+fn href_for(path: &str) -> String {
+    match MODE.with(Cell::get) {
+        HistoryMode::History => path.to_owned(),
+        HistoryMode::Hash => format!("#{path}"),
+    }
+}
+
+/// The current path, with the query string.
+pub fn current_path() -> String {
+    LOCATION.with(|location| location.borrow().clone())
+}
+
+/// Navigate to the given path, adding an entry to the browser history.
+pub fn navigate(path: &str) {
+    WINDOW.with(|window| {
+        window
+            .history()
+            .unwrap_throw()
+            .push_state_with_url(&JsValue::NULL, "", Some(&href_for(path)))
+            .unwrap_throw()
+    });
+    set_location(path.to_owned());
+}
+
+/// Navigate to the given path, replacing the current entry in the browser history.
+pub fn navigate_replace(path: &str) {
+    WINDOW.with(|window| {
+        window
+            .history()
+            .unwrap_throw()
+            .replace_state_with_url(&JsValue::NULL, "", Some(&href_for(path)))
+            .unwrap_throw()
+    });
+    set_location(path.to_owned());
+}
+
+/**
+An `<a>` that navigates with the [Router] instead of loading a new page.
+
+Clicks with a modifier key held (to open in a new tab, for example) and links
+with a `target` attribute are left to the browser.
+
+```
+# use async_ui_web::{prelude_traits::*, router::Link};
+# let _ = async {
+let link = Link::new("/about");
+link.render("About us".render()).await;
+# };
+```
+
+This type [Deref]s to [Anchor], so you can use all the HTML methods on it.
+*/
+pub struct Link {
+    anchor: Anchor,
+    path: RefCell<String>,
+}
+
+impl Deref for Link {
+    type Target = Anchor;
+    fn deref(&self) -> &Self::Target {
+        &self.anchor
+    }
+}
+
+impl Link {
+    /// Create a link to the given path.
+    pub fn new(path: &str) -> Self {
+        let anchor = Anchor::new();
+        anchor.set_href(&href_for(path));
+        Self {
+            anchor,
+            path: RefCell::new(path.to_owned()),
+        }
+    }
+    /// Change where the link goes.
+    pub fn set_path(&self, path: &str) {
+        self.anchor.set_href(&href_for(path));
+        *self.path.borrow_mut() = path.to_owned();
+    }
+    /// Render the link, with the given content.
+    ///
+    /// This completes when `children` completes, with the same output.
+    pub async fn render<F: Future>(&self, children: F) -> F::Output {
+        // the mode may have been set after this link was created
+        self.anchor.set_href(&href_for(&self.path.borrow()));
+        race((self.anchor.render(children), async {
+            let mut clicks = self.anchor.until_click();
+            loop {
+                let ev = (&mut clicks).await;
+                let modified = ev.ctrl_key() || ev.shift_key() || ev.alt_key() || ev.meta_key();
+                if ev.button() != 0 || modified || self.anchor.has_attribute("target") {
+                    continue;
+                }
+                ev.prevent_default();
+                navigate(&self.path.borrow());
+            }
+        }))
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments(path: &str) -> Vec<String> {
+        split_path(path).map(String::from).collect()
+    }
+
+    #[test]
+    fn nested_matching() {
+        let routes = Routes::new()
+            .route(Route::new("/", |_| async {}))
+            .route(
+                Route::new("/teams/:team", |_| async {}).children(
+                    Routes::new()
+                        .route(Route::new("/members/:member", |_| async {}))
+                        .fallback(|_| async {}),
+                ),
+            )
+            .route(Route::new("/about", |_| async {}));
+        let matched = |path: &str| {
+            routes
+                .match_path(&segments(path), &Params::default())
+                .map(|chain| chain.into_iter().map(|m| m.params).collect::<Vec<_>>())
+        };
+
+        assert_eq!(matched("/").map(|chain| chain.len()), Some(1));
+        assert_eq!(matched("/about").map(|chain| chain.len()), Some(1));
+        assert_eq!(matched("/nowhere"), None);
+
+        let chain = matched("/teams/7/members/alice").unwrap();
+        assert_eq!(chain.len(), 2);
+        assert_eq!(chain[0].get::<u32>("team"), Some(7));
+        assert_eq!(chain[0].get_str("member"), None);
+        assert_eq!(chain[1].get::<u32>("team"), Some(7));
+        assert_eq!(chain[1].get_str("member"), Some("alice"));
+
+        // the parent alone, and the parent with the child fallback
+        assert_eq!(matched("/teams/7").map(|chain| chain.len()), Some(1));
+        assert_eq!(
+            matched("/teams/7/unknown").map(|chain| chain.len()),
+            Some(2)
+        );
+    }
+}
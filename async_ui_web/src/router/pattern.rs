@@ -0,0 +1,193 @@
+use std::str::FromStr;
+
+/// A parsed route pattern, like `/users/:id/*rest`.
+///
+/// *   A plain segment (`users`) matches itself.
+/// *   A segment starting with `:` (`:id`) matches any one segment,
+///     and captures it as a parameter.
+/// *   A segment starting with `*` (`*rest`) matches all the remaining segments
+///     (even none), and captures them as a parameter. It must be last.
+///
+/// Empty segments are ignored, so `/users/`, `users`, and `/users` are the same.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct Pattern {
+    segments: Vec<Segment>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    Static(String),
+    Param(String),
+    Wildcard(String),
+}
+
+impl Pattern {
+    /// Parse a pattern.
+    ///
+    /// Panics if a wildcard segment is not the last.
+    pub(super) fn parse(pattern: &str) -> Self {
+        let segments: Vec<Segment> = split_path(pattern)
+            .map(|segment| {
+                if let Some(name) = segment.strip_prefix(':') {
+                    Segment::Param(name.to_owned())
+                } else if let Some(name) = segment.strip_prefix('*') {
+                    Segment::Wildcard(name.to_owned())
+                } else {
+                    Segment::Static(segment.to_owned())
+                }
+            })
+            .collect();
+        if let Some(pos) = segments
+            .iter()
+            .position(|s| matches!(s, Segment::Wildcard(_)))
+        {
+            assert!(
+                pos + 1 == segments.len(),
+                "wildcard must be the last segment of route pattern {pattern:?}"
+            );
+        }
+        Self { segments }
+    }
+    /// Match the start of `path` (a list of segments) against this pattern,
+    /// adding captured parameters to `params`.
+    ///
+    /// Returns the number of segments consumed, or None if there is no match.
+    /// If `exact`, all of `path` must be consumed.
+    pub(super) fn match_segments(
+        &self,
+        path: &[String],
+        exact: bool,
+        params: &mut Params,
+    ) -> Option<usize> {
+        let mut captured = Vec::new();
+        let mut consumed = 0;
+        for segment in self.segments.iter() {
+            match segment {
+                Segment::Static(expected) => {
+                    if path.get(consumed) != Some(expected) {
+                        return None;
+                    }
+                    consumed += 1;
+                }
+                Segment::Param(name) => {
+                    captured.push((name.clone(), path.get(consumed)?.clone()));
+                    consumed += 1;
+                }
+                Segment::Wildcard(name) => {
+                    captured.push((name.clone(), path[consumed..].join("/")));
+                    consumed = path.len();
+                }
+            }
+        }
+        if exact && consumed != path.len() {
+            return None;
+        }
+        params.0.extend(captured);
+        Some(consumed)
+    }
+}
+
+/// Split a path into its non-empty segments.
+pub(super) fn split_path(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|s| !s.is_empty())
+}
+
+/// Parameters captured from the path by the `:name` and `*name` segments of
+/// route patterns.
+///
+/// Parameters of outer (parent) routes are included.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Params(Vec<(String, String)>);
+
+impl Params {
+    /// Get the parameter with the given name, parsed into the type you want.
+    ///
+    /// Returns None if there is no such parameter, or if it fails to parse.
+    /// ```
+    /// # use async_ui_web::router::Params;
+    /// # fn f(params: &Params) -> Option<()> {
+    /// let user_id: u64 = params.get("id")?;
+    /// # None
+    /// # }
+    /// ```
+    pub fn get<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.get_str(name)?.parse().ok()
+    }
+    /// Get the parameter with the given name, as a string.
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        // the innermost route wins if names clash
+        self.0
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+    /// Iterate over all the parameters, as `(name, value)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(n, v)| (n.as_str(), v.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments(path: &str) -> Vec<String> {
+        split_path(path).map(String::from).collect()
+    }
+
+    #[test]
+    fn static_and_params() {
+        let pattern = Pattern::parse("/users/:id/posts/:post");
+        let mut params = Params::default();
+        assert_eq!(
+            pattern.match_segments(&segments("/users/42/posts/hello"), true, &mut params),
+            Some(4)
+        );
+        assert_eq!(params.get::<u32>("id"), Some(42));
+        assert_eq!(params.get_str("post"), Some("hello"));
+        assert_eq!(params.get::<u32>("post"), None);
+        assert_eq!(
+            pattern.match_segments(&segments("/users/42"), true, &mut Params::default()),
+            None
+        );
+        assert_eq!(
+            pattern.match_segments(&segments("/teams/42/posts/x"), true, &mut Params::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn prefix_and_wildcard() {
+        let pattern = Pattern::parse("settings");
+        let path = segments("/settings/profile/");
+        assert_eq!(
+            pattern.match_segments(&path, true, &mut Params::default()),
+            None
+        );
+        assert_eq!(
+            pattern.match_segments(&path, false, &mut Params::default()),
+            Some(1)
+        );
+
+        let pattern = Pattern::parse("/files/*path");
+        let mut params = Params::default();
+        assert_eq!(
+            pattern.match_segments(&segments("/files/a/b.txt"), true, &mut params),
+            Some(3)
+        );
+        assert_eq!(params.get_str("path"), Some("a/b.txt"));
+
+        let root = Pattern::parse("/");
+        assert_eq!(
+            root.match_segments(&[], true, &mut Params::default()),
+            Some(0)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn wildcard_must_be_last() {
+        Pattern::parse("/*rest/more");
+    }
+}
@@ -0,0 +1,284 @@
+use std::{rc::Rc, str::FromStr};
+
+use async_ui_web_core::window::WINDOW;
+use async_ui_web_html::events::EmitEvent;
+use futures_lite::StreamExt;
+
+use super::{navigate, navigate_replace, sync_location, LOCATION};
+use crate::{race, reactive::ReactiveMut};
+
+/// How [bind_query] records changes of the state in the browser history.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QueryHistory {
+    /// Replace the current history entry
+    /// (with [replaceState](https://developer.mozilla.org/en-US/docs/Web/API/History/replaceState)).
+    /// The back button skips over the changes.
+    #[default]
+    Replace,
+    /// Add a history entry for every change
+    /// (with [pushState](https://developer.mozilla.org/en-US/docs/Web/API/History/pushState)).
+    /// The back button undoes the changes one by one.
+    Push,
+}
+
+/// Get the value of a query parameter in the current URL.
+pub fn query_param(name: &str) -> Option<String> {
+    let location = LOCATION.with(|location| location.borrow().clone());
+    get_query_param(&location, name)
+}
+
+/**
+Keep some state in sync with a query parameter of the URL, in both directions.
+
+This keeps things like filters and search terms when the page is reloaded,
+and puts them in the URL when it is shared.
+
+*   When this starts, and every time the URL changes (including when the user
+    goes back or forward), the state is set from the query parameter.
+    If the parameter is missing or doesn't parse, the state is set to `default`.
+*   When the state changes, the URL is updated. `history` chooses whether this
+    adds a history entry. If the state is equal to `default`, the parameter is
+    removed from the URL.
+
+The state can be anything [ReactiveMut], such as a [ReactiveCell][crate::ReactiveCell]
+or an X-Bow path.
+
+```
+# use async_ui_web::{join, router::{bind_query, QueryHistory}, ReactiveCell};
+# async fn product_list(search: &ReactiveCell<String>, page: &ReactiveCell<u32>) {}
+# let _ = async {
+let search = ReactiveCell::new(String::new());
+let page = ReactiveCell::new(1u32);
+// the URL will look like `/products?q=chair&page=2`
+join((
+    bind_query("q", &search, String::new(), QueryHistory::Replace),
+    bind_query("page", &page, 1, QueryHistory::Push),
+    product_list(&search, &page),
+))
+.await;
+# };
+```
+
+This async function never completes.
+*/
+pub async fn bind_query<S, T>(name: &str, state: &S, default: T, history: QueryHistory)
+where
+    S: ReactiveMut<Value = T> + ?Sized,
+    T: FromStr + ToString + PartialEq + Clone,
+{
+    let location = LOCATION.with(Rc::clone);
+    sync_location();
+    let read_url = || {
+        let value = parse_param(query_param(name), &default);
+        if state.with_value(|old| old != &value).unwrap_or(true) {
+            state.set_value(value);
+        }
+    };
+    read_url();
+    let mut state_changes = state.until_value_change();
+    let mut location_changes = location.until_change();
+    let mut popstates = WINDOW.with(|w| w.until_event::<web_sys::Event>("popstate".into()));
+    let mut hashchanges = WINDOW.with(|w| w.until_event::<web_sys::Event>("hashchange".into()));
+    race((
+        async {
+            while state_changes.next().await.is_some() {
+                let Some(value) = state.with_value(|v| serialize_param(v, &default)) else {
+                    continue;
+                };
+                let current = location.borrow().clone();
+                let new = with_query_param(&current, name, value.as_deref());
+                if new != current {
+                    match history {
+                        QueryHistory::Replace => navigate_replace(&new),
+                        QueryHistory::Push => navigate(&new),
+                    }
+                }
+            }
+        },
+        async {
+            loop {
+                race((
+                    async {
+                        location_changes.next().await;
+                    },
+                    async {
+                        (&mut popstates).await;
+                    },
+                    async {
+                        (&mut hashchanges).await;
+                    },
+                ))
+                .await;
+                // there may be no router to pick up `popstate`
+                sync_location();
+                read_url();
+            }
+        },
+    ))
+    .await
+}
+
+/// The state for a query parameter value; `default` if it is missing or doesn't parse.
+fn parse_param<T: FromStr + Clone>(value: Option<String>, default: &T) -> T {
+    value
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_else(|| default.clone())
+}
+
+/// The query parameter value for a state; `None` (no parameter) if it is `default`.
+fn serialize_param<T: ToString + PartialEq>(value: &T, default: &T) -> Option<String> {
+    (value != default).then(|| value.to_string())
+}
+
+/// The query string of a location (a path with a query string), without the `?`.
+fn query_string(location: &str) -> &str {
+    location.split_once('?').map_or("", |(_, query)| query)
+}
+
+/// Get the (decoded) value of a query parameter in a location.
+/// If the parameter appears more than once, the first one is used.
+fn get_query_param(location: &str, name: &str) -> Option<String> {
+    query_string(location)
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+        .find(|(key, _)| decode(key) == name)
+        .map(|(_, value)| decode(value))
+}
+
+/// Set (or with `None`, remove) a query parameter in a location.
+///
+/// Like [URLSearchParams.set](https://developer.mozilla.org/en-US/docs/Web/API/URLSearchParams/set),
+/// this replaces the first occurrence of the parameter and removes the others,
+/// or appends the parameter if it is missing. Other parameters are kept as they are.
+fn with_query_param(location: &str, name: &str, value: Option<&str>) -> String {
+    let path = location.split_once('?').map_or(location, |(path, _)| path);
+    let mut new = value.map(|value| format!("{}={}", encode(name), encode(value)));
+    let mut pairs = Vec::new();
+    for pair in query_string(location)
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+    {
+        let key = pair.split_once('=').map_or(pair, |(key, _)| key);
+        if decode(key) == name {
+            pairs.extend(new.take());
+        } else {
+            pairs.push(pair.to_owned());
+        }
+    }
+    pairs.extend(new);
+    if pairs.is_empty() {
+        path.to_owned()
+    } else {
+        format!("{path}?{}", pairs.join("&"))
+    }
+}
+
+/// Encode a query string component, as `application/x-www-form-urlencoded`.
+fn encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'*' | b'-' | b'.' | b'_' => {
+                encoded.push(byte as char)
+            }
+            b' ' => encoded.push('+'),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// Decode a query string component, as `application/x-www-form-urlencoded`.
+/// Invalid escapes are kept as they are.
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = || {
+            let digits = std::str::from_utf8(bytes.get(index + 1..index + 3)?).ok()?;
+            u8::from_str_radix(digits, 16).ok()
+        };
+        match (bytes[index], hex()) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+                continue;
+            }
+            (b'+', _) => decoded.push(b' '),
+            (byte, _) => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_params() {
+        let location = "/products?q=red+chair&page=2&empty&q=ignored";
+        assert_eq!(get_query_param(location, "q").as_deref(), Some("red chair"));
+        assert_eq!(get_query_param(location, "page").as_deref(), Some("2"));
+        assert_eq!(get_query_param(location, "empty").as_deref(), Some(""));
+        assert_eq!(get_query_param(location, "missing"), None);
+        assert_eq!(get_query_param("/products", "q"), None);
+        assert_eq!(
+            get_query_param("/?name=Z%C3%BCrich%20%26%20more", "name").as_deref(),
+            Some("Zürich & more")
+        );
+        // invalid escapes are kept
+        assert_eq!(get_query_param("/?a=100%", "a").as_deref(), Some("100%"));
+    }
+
+    #[test]
+    fn set_params() {
+        assert_eq!(
+            with_query_param("/products", "q", Some("chair")),
+            "/products?q=chair"
+        );
+        assert_eq!(
+            with_query_param("/products?q=chair&page=2", "q", Some("red sofa & co")),
+            "/products?q=red+sofa+%26+co&page=2"
+        );
+        assert_eq!(
+            with_query_param("/products?page=2", "q", Some("x")),
+            "/products?page=2&q=x"
+        );
+        assert_eq!(
+            with_query_param("/products?q=a&page=2&q=b", "q", Some("c")),
+            "/products?q=c&page=2"
+        );
+        assert_eq!(
+            with_query_param("/products?q=a&page=2&q=b", "q", None),
+            "/products?page=2"
+        );
+        assert_eq!(with_query_param("/products?q=a", "q", None), "/products");
+    }
+
+    #[test]
+    fn round_trip() {
+        for text in ["plain", "with space", "a+b=c&d", "100%", "Zürich", "🙂", ""] {
+            let location = with_query_param("/", "text", Some(text));
+            assert_eq!(get_query_param(&location, "text").as_deref(), Some(text));
+        }
+    }
+
+    #[test]
+    fn default_removes_param() {
+        assert_eq!(serialize_param(&1u32, &1), None);
+        assert_eq!(serialize_param(&2u32, &1).as_deref(), Some("2"));
+        assert_eq!(parse_param(Some("2".into()), &1u32), 2);
+        assert_eq!(parse_param(Some("two".into()), &1u32), 1);
+        assert_eq!(parse_param(None, &1u32), 1);
+
+        // setting the state back to the default removes the parameter
+        let location = "/products?page=3&q=chair";
+        let location = with_query_param(location, "page", serialize_param(&1u32, &1).as_deref());
+        assert_eq!(location, "/products?q=chair");
+        assert_eq!(parse_param(get_query_param(&location, "page"), &1u32), 1);
+    }
+}
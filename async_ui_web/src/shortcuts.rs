@@ -1,10 +1,16 @@
-use std::future::Pending;
+use std::{
+    fmt::Display,
+    future::{Future, Pending},
+};
 
-use async_ui_web_core::ContainerNodeFuture;
+use async_ui_web_core::{combinators::join, ContainerNodeFuture};
 use async_ui_web_html::nodes::Text;
+use futures_lite::{stream, Stream, StreamExt};
 use js_sys::Array;
 use wasm_bindgen::{JsValue, UnwrapThrowExt};
 
+use crate::reactive::Reactive;
+
 pub trait ShortcutRenderStr {
     /// Render the [str] as an HTML text node with that content.
     ///
@@ -24,6 +30,63 @@ impl ShortcutRenderStr for str {
     }
 }
 
+pub trait ShortcutRenderReactive: Reactive {
+    /// Render the value as an HTML text node, formatted with [Display].
+    /// The text is updated whenever the value changes.
+    ///
+    /// Works for [ReactiveCell][crate::ReactiveCell], x-bow paths (wrapped in `XBowPath`),
+    /// and anything else implementing [Reactive].
+    ///
+    /// ```
+    /// # use async_ui_web::{shortcut_traits::ShortcutRenderReactive, ReactiveCell};
+    /// # let _ = async {
+    /// let count = ReactiveCell::new(0);
+    /// count.render_reactive().await;
+    /// # };
+    /// ```
+    fn render_reactive(&self) -> impl Future<Output = ()> + '_;
+}
+impl<R: Reactive + ?Sized> ShortcutRenderReactive for R
+where
+    R::Value: Display,
+{
+    fn render_reactive(&self) -> impl Future<Output = ()> + '_ {
+        let text = Text::new();
+        let values = stream::once(()).chain(self.until_value_change()).map(|_| {
+            self.with_value(|value| value.to_string())
+                .unwrap_or_default()
+        });
+        async move {
+            join((text.render(), text.bind(values))).await;
+        }
+    }
+}
+
+pub trait ShortcutRenderStream: Stream + Sized {
+    /// Render each item of the stream as an HTML text node, formatted with [Display].
+    ///
+    /// The text stays on the screen after the stream ends.
+    ///
+    /// ```
+    /// # use async_ui_web::shortcut_traits::ShortcutRenderStream;
+    /// # let _ = async {
+    /// futures_lite::stream::iter([1, 2, 3]).render_stream().await;
+    /// # };
+    /// ```
+    fn render_stream(self) -> impl Future<Output = ()>;
+}
+impl<S: Stream> ShortcutRenderStream for S
+where
+    S::Item: Display,
+{
+    fn render_stream(self) -> impl Future<Output = ()> {
+        let text = Text::new();
+        async move {
+            join((text.render(), text.bind(self))).await;
+        }
+    }
+}
+
 pub trait ShortcutClassList {
     /// Add a classname to this element, if not already present.
     ///
@@ -0,0 +1,434 @@
+/*!
+Typed inline styles.
+
+Build a [Styles] with typed property setters, then apply it to an element with
+[ShortcutStyle::set_styles] (or [with_styles][ShortcutStyle::with_styles]).
+
+```
+# use async_ui_web::{html::Div, prelude_traits::*, style::{deg, percent, px, rem, Color, Styles, Transform}};
+# let _ = async {
+# let children = std::future::pending::<()>();
+Div::new()
+    .with_styles(
+        &Styles::new()
+            .width(percent(50.0))
+            .padding(rem(1.0))
+            .background_color(Color::hex(0x336699))
+            .transform(Transform::new().translate(px(10.0), px(0.0)).rotate(deg(45.0)))
+            // escape hatch for properties without a typed setter
+            .property("scroll-snap-type", "x mandatory"),
+    )
+    .render(children)
+    .await;
+# };
+```
+
+For styles that follow some state, see [bind_styles][ShortcutStyle::bind_styles].
+*/
+
+use std::{borrow::Cow, fmt::Display, future::Future};
+
+use futures_lite::StreamExt;
+use wasm_bindgen::UnwrapThrowExt;
+use web_sys::HtmlElement;
+
+use crate::reactive::Reactive;
+
+/// A CSS length.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Length {
+    /// Pixels (`px`).
+    Px(f64),
+    /// Percentage of the containing block (`%`).
+    Percent(f64),
+    /// Multiples of the root element font size (`rem`).
+    Rem(f64),
+    /// Multiples of the element font size (`em`).
+    Em(f64),
+    /// Percentage of the viewport width (`vw`).
+    Vw(f64),
+    /// Percentage of the viewport height (`vh`).
+    Vh(f64),
+    /// The `auto` keyword.
+    Auto,
+}
+
+/// Shorthand for [Length::Px].
+pub fn px(value: f64) -> Length {
+    Length::Px(value)
+}
+/// Shorthand for [Length::Percent].
+pub fn percent(value: f64) -> Length {
+    Length::Percent(value)
+}
+/// Shorthand for [Length::Rem].
+pub fn rem(value: f64) -> Length {
+    Length::Rem(value)
+}
+/// Shorthand for [Length::Em].
+pub fn em(value: f64) -> Length {
+    Length::Em(value)
+}
+
+impl Display for Length {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Px(v) => write!(f, "{v}px"),
+            Self::Percent(v) => write!(f, "{v}%"),
+            Self::Rem(v) => write!(f, "{v}rem"),
+            Self::Em(v) => write!(f, "{v}em"),
+            Self::Vw(v) => write!(f, "{v}vw"),
+            Self::Vh(v) => write!(f, "{v}vh"),
+            Self::Auto => f.write_str("auto"),
+        }
+    }
+}
+
+/// A CSS angle, used in [Transform]s.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Angle {
+    /// Degrees (`deg`).
+    Deg(f64),
+    /// Radians (`rad`).
+    Rad(f64),
+    /// Full turns (`turn`).
+    Turn(f64),
+}
+
+/// Shorthand for [Angle::Deg].
+pub fn deg(value: f64) -> Angle {
+    Angle::Deg(value)
+}
+
+impl Display for Angle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Deg(v) => write!(f, "{v}deg"),
+            Self::Rad(v) => write!(f, "{v}rad"),
+            Self::Turn(v) => write!(f, "{v}turn"),
+        }
+    }
+}
+
+/// A CSS color.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Color {
+    /// Red, green, and blue channels.
+    Rgb(u8, u8, u8),
+    /// Red, green, blue, and alpha (from 0.0 to 1.0).
+    Rgba(u8, u8, u8, f64),
+    /// Hue (in degrees), saturation and lightness (in percent).
+    Hsl(f64, f64, f64),
+    /// Hue (in degrees), saturation and lightness (in percent), and alpha (from 0.0 to 1.0).
+    Hsla(f64, f64, f64, f64),
+    /// A named color or keyword, like `rebeccapurple`, `transparent`, or `currentcolor`,
+    /// or a custom property reference like `var(--accent)`.
+    Named(Cow<'static, str>),
+}
+
+impl Color {
+    /// Create an RGB color from a hex number like `0xff8800`.
+    pub fn hex(rgb: u32) -> Self {
+        Self::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+    }
+}
+
+impl Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rgb(r, g, b) => write!(f, "rgb({r}, {g}, {b})"),
+            Self::Rgba(r, g, b, a) => write!(f, "rgba({r}, {g}, {b}, {a})"),
+            Self::Hsl(h, s, l) => write!(f, "hsl({h}, {s}%, {l}%)"),
+            Self::Hsla(h, s, l, a) => write!(f, "hsla({h}, {s}%, {l}%, {a})"),
+            Self::Named(name) => f.write_str(name),
+        }
+    }
+}
+
+/// A list of CSS transform functions, applied in order.
+///
+/// ```
+/// # use async_ui_web::style::{deg, px, Transform};
+/// let t = Transform::new().translate(px(10.0), px(5.0)).rotate(deg(90.0)).scale(2.0);
+/// assert_eq!(t.to_string(), "translate(10px, 5px) rotate(90deg) scale(2)");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Transform {
+    functions: Vec<String>,
+}
+
+impl Transform {
+    /// Create an empty transform (equivalent to `none`).
+    pub fn new() -> Self {
+        Self::default()
+    }
+    fn push(mut self, function: String) -> Self {
+        self.functions.push(function);
+        self
+    }
+    /// Append `translate(x, y)`.
+    pub fn translate(self, x: Length, y: Length) -> Self {
+        self.push(format!("translate({x}, {y})"))
+    }
+    /// Append `translateX(x)`.
+    pub fn translate_x(self, x: Length) -> Self {
+        self.push(format!("translateX({x})"))
+    }
+    /// Append `translateY(y)`.
+    pub fn translate_y(self, y: Length) -> Self {
+        self.push(format!("translateY({y})"))
+    }
+    /// Append `scale(factor)`.
+    pub fn scale(self, factor: f64) -> Self {
+        self.push(format!("scale({factor})"))
+    }
+    /// Append `scale(x, y)`.
+    pub fn scale_xy(self, x: f64, y: f64) -> Self {
+        self.push(format!("scale({x}, {y})"))
+    }
+    /// Append `rotate(angle)`.
+    pub fn rotate(self, angle: Angle) -> Self {
+        self.push(format!("rotate({angle})"))
+    }
+    /// Append `skewX(angle)`.
+    pub fn skew_x(self, angle: Angle) -> Self {
+        self.push(format!("skewX({angle})"))
+    }
+    /// Append `skewY(angle)`.
+    pub fn skew_y(self, angle: Angle) -> Self {
+        self.push(format!("skewY({angle})"))
+    }
+}
+
+impl Display for Transform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.functions.is_empty() {
+            return f.write_str("none");
+        }
+        f.write_str(&self.functions.join(" "))
+    }
+}
+
+/// A batch of inline style declarations.
+///
+/// Nothing is written to the DOM until the batch is applied with
+/// [ShortcutStyle::set_styles], which writes the whole batch in one `cssText` update.
+/// Inline properties that aren't in the batch are left as they are.
+///
+/// Setting the same property twice keeps only the last value.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Styles {
+    declarations: Vec<(Cow<'static, str>, String)>,
+}
+
+macro_rules! style_setters {
+    ($($method:ident, $name:literal, $ty:ty;)*) => {
+        $(
+            #[doc = "Set the `"]
+            #[doc = $name]
+            #[doc = "` property."]
+            pub fn $method(self, value: $ty) -> Self {
+                self.property($name, value)
+            }
+        )*
+    };
+}
+
+impl Styles {
+    /// Create an empty batch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Set any property, including custom properties (`--my-var`).
+    ///
+    /// Use this for properties that don't have a typed setter.
+    pub fn property(mut self, name: impl Into<Cow<'static, str>>, value: impl Display) -> Self {
+        let name = name.into();
+        let value = value.to_string();
+        match self.declarations.iter_mut().find(|(n, _)| *n == name) {
+            Some(existing) => existing.1 = value,
+            None => self.declarations.push((name, value)),
+        }
+        self
+    }
+    style_setters! {
+        width, "width", Length;
+        height, "height", Length;
+        min_width, "min-width", Length;
+        min_height, "min-height", Length;
+        max_width, "max-width", Length;
+        max_height, "max-height", Length;
+        inline_size, "inline-size", Length;
+        block_size, "block-size", Length;
+        top, "top", Length;
+        right, "right", Length;
+        bottom, "bottom", Length;
+        left, "left", Length;
+        margin, "margin", Length;
+        padding, "padding", Length;
+        gap, "gap", Length;
+        font_size, "font-size", Length;
+        border_radius, "border-radius", Length;
+        opacity, "opacity", f64;
+        z_index, "z-index", i32;
+        color, "color", Color;
+        background_color, "background-color", Color;
+        border_color, "border-color", Color;
+        transform, "transform", Transform;
+    }
+    /// The property names in this batch.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.declarations.iter().map(|(name, _)| &**name)
+    }
+    /// Append the batch to an existing declaration list.
+    /// Later declarations win, so the batch overrides properties it shares
+    /// with `css_text`.
+    fn append_to(&self, css_text: &str) -> String {
+        let css_text = css_text.trim();
+        if css_text.is_empty() {
+            self.to_string()
+        } else if css_text.ends_with(';') {
+            format!("{css_text} {self}")
+        } else {
+            format!("{css_text}; {self}")
+        }
+    }
+}
+
+/// Formats as a CSS declaration list, like `width: 10px; opacity: 0.5;`.
+impl Display for Styles {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx, (name, value)) in self.declarations.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{name}: {value};")?;
+        }
+        Ok(())
+    }
+}
+
+pub trait ShortcutStyle: AsRef<HtmlElement> {
+    /// Set one inline style property.
+    ///
+    /// This method is equivalent to
+    /// `elem.style().set_property(name, &value.to_string()).unwrap_throw()`.
+    fn set_style(&self, name: &str, value: impl Display) {
+        self.as_ref()
+            .style()
+            .set_property(name, &value.to_string())
+            .unwrap_throw();
+    }
+    /// Remove one inline style property.
+    fn del_style(&self, name: &str) {
+        self.as_ref().style().remove_property(name).unwrap_throw();
+    }
+    /// Write all the properties in the batch to the inline style of this element.
+    ///
+    /// The batch is written with a single
+    /// [cssText](web_sys::CssStyleDeclaration::set_css_text) update,
+    /// so the style attribute only changes once.
+    fn set_styles(&self, styles: &Styles) {
+        if styles.declarations.is_empty() {
+            return;
+        }
+        let style = self.as_ref().style();
+        style.set_css_text(&styles.append_to(&style.css_text()));
+    }
+    /// Apply the batch to the element and return reference to the input.
+    ///
+    /// This is for writing the UI "declaratively", like
+    /// [with_class][crate::shortcut_traits::ShortcutClassListBuilder::with_class].
+    fn with_styles(&self, styles: &Styles) -> &Self {
+        self.set_styles(styles);
+        self
+    }
+    /// Keep the inline style of this element in sync with some [Reactive] state.
+    ///
+    /// The given function converts the state into a [Styles] batch.
+    /// It is called once at the start and again every time the state changes.
+    /// Properties that were set by a previous batch but are missing from the
+    /// new one are removed.
+    ///
+    /// The returned Future never completes.
+    ///
+    /// ```
+    /// # use async_ui_web::{html::Div, join, prelude_traits::*, style::{percent, Styles}, NoChild, ReactiveCell};
+    /// # let _ = async {
+    /// let progress = ReactiveCell::new(0.0);
+    /// let bar = Div::new();
+    /// join((
+    ///     bar.render(NoChild),
+    ///     bar.bind_styles(&progress, |p| Styles::new().width(percent(p * 100.0))),
+    /// ))
+    /// .await;
+    /// # };
+    /// ```
+    fn bind_styles<'a, R: Reactive + ?Sized>(
+        &'a self,
+        source: &'a R,
+        mut to_styles: impl FnMut(&R::Value) -> Styles + 'a,
+    ) -> impl Future<Output = ()> + 'a {
+        async move {
+            let mut changes = source.until_value_change();
+            let mut previous = Styles::new();
+            loop {
+                let styles = source.with_value(&mut to_styles).unwrap_or_default();
+                previous
+                    .names()
+                    .filter(|name| !styles.names().any(|n| n == *name))
+                    .for_each(|name| self.del_style(name));
+                self.set_styles(&styles);
+                previous = styles;
+                changes.next().await;
+            }
+        }
+    }
+}
+impl<T: AsRef<HtmlElement>> ShortcutStyle for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_values() {
+        assert_eq!(px(1.5).to_string(), "1.5px");
+        assert_eq!(percent(50.0).to_string(), "50%");
+        assert_eq!(Length::Auto.to_string(), "auto");
+        assert_eq!(Color::hex(0xff8800).to_string(), "rgb(255, 136, 0)");
+        assert_eq!(
+            Color::Hsla(120.0, 50.0, 25.0, 0.5).to_string(),
+            "hsla(120, 50%, 25%, 0.5)"
+        );
+        assert_eq!(Transform::new().to_string(), "none");
+    }
+
+    #[test]
+    fn batch() {
+        let styles = Styles::new()
+            .width(px(10.0))
+            .opacity(0.5)
+            .property("--accent", Color::Named("red".into()))
+            .width(rem(2.0));
+        assert_eq!(
+            styles.to_string(),
+            "width: 2rem; opacity: 0.5; --accent: red;"
+        );
+        assert_eq!(
+            styles.names().collect::<Vec<_>>(),
+            ["width", "opacity", "--accent"]
+        );
+    }
+
+    #[test]
+    fn append() {
+        let styles = Styles::new().width(px(10.0));
+        assert_eq!(styles.append_to(""), "width: 10px;");
+        assert_eq!(
+            styles.append_to("color: red; width: 5px;"),
+            "color: red; width: 5px; width: 10px;"
+        );
+        assert_eq!(styles.append_to("color: red"), "color: red; width: 10px;");
+    }
+}
@@ -21,7 +21,7 @@ pub trait UiFutureExt: Future + Sized {
     /// `f.pend_after()` is equivalent to
     /// ```rust
     /// # let f = async {};
-    /// # async { let _: i32 = 
+    /// # async { let _: i32 =
     /// async {
     /// 	f.await;
     /// 	std::future::pending().await
@@ -47,7 +47,7 @@ pub trait UiFutureExt: Future + Sized {
     /// ))
     /// # };
     /// ```
-    /// 
+    ///
     /// Use this to display UI as side-effect of some async execution.
     /// For example, `load_data().meanwhile(spinner()).await`.
     fn meanwhile<F: Future>(
@@ -5,9 +5,11 @@ pub mod window;
 mod context;
 mod dropping;
 mod node_container;
+mod node_fragment;
 mod node_sibling;
 mod position;
 
 pub use dropping::DetachmentBlocker;
 pub use node_container::ContainerNodeFuture;
+pub use node_fragment::FragmentNodeFuture;
 pub use node_sibling::SiblingNodeFuture;
@@ -0,0 +1,70 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use pin_project::{pin_project, pinned_drop};
+
+use crate::{
+    context::{DomContext, DOM_CONTEXT},
+    dropping::DetachmentBlocker,
+    position::ChildPosition,
+};
+
+/// Future that puts a list of existing nodes on the screen, in order,
+/// without a wrapper element. This is like inserting a `DocumentFragment`,
+/// except the nodes are also removed together when the future is dropped.
+///
+/// Upon first poll of the future the nodes will be added to the parent.
+/// The future never completes.
+#[pin_project(PinnedDrop)]
+pub struct FragmentNodeFuture {
+    nodes: Vec<web_sys::Node>,
+    added: bool,
+    drop: DetachmentBlocker,
+}
+
+impl FragmentNodeFuture {
+    pub fn new(nodes: Vec<web_sys::Node>) -> Self {
+        Self {
+            nodes,
+            added: false,
+            drop: DetachmentBlocker,
+        }
+    }
+}
+
+impl Future for FragmentNodeFuture {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        if !*this.added {
+            *this.added = true;
+            DOM_CONTEXT.with(|parent| {
+                for (index, node) in this.nodes.iter().enumerate() {
+                    // each node gets its own position inside ours,
+                    // so removing ours removes all of them
+                    DomContext::Child {
+                        parent,
+                        index: index as u32,
+                    }
+                    .add_child(ChildPosition::default(), node.clone());
+                }
+            });
+        }
+        Poll::Pending
+    }
+}
+
+#[pinned_drop]
+impl PinnedDrop for FragmentNodeFuture {
+    fn drop(self: Pin<&mut Self>) {
+        if self.added && !self.drop.block_until_drop() {
+            DOM_CONTEXT.with(|ctx| {
+                ctx.remove_child(ChildPosition::default());
+            })
+        }
+    }
+}
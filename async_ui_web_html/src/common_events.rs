@@ -64,6 +64,42 @@ pub trait EmitElementEvent: AsRef<Element> {
     // make_event_impl!("cut", event_cut, web_sys::ClipboardEvent, "[MDN documentation for this event](https://developer.mozilla.org/en-US/docs/Web/API/Element/cut_event)");
     // make_event_impl!("paste", event_paste, web_sys::ClipboardEvent, "[MDN documentation for this event](https://developer.mozilla.org/en-US/docs/Web/API/Element/paste_event)");
 
+    /// Like [until_keydown][EmitElementEvent::until_keydown], but only fires
+    /// for the given [key](https://developer.mozilla.org/en-US/docs/Web/API/UI_Events/Keyboard_event_key_values)
+    /// (e.g. `"Enter"`, `"Escape"`, `"a"`).
+    ///
+    /// Other keys are ignored without waking the executor.
+    /// ```
+    /// # use async_ui_web_html::{events::EmitElementEvent, nodes::Input};
+    /// # let _ = async {
+    /// # let input = Input::new();
+    /// input.until_key("Enter").await;
+    /// // submit
+    /// # };
+    /// ```
+    #[must_use = "the returned object is a Future+Stream that does nothing unless polled"]
+    fn until_key(&self, key: &str) -> EventFutureStream<web_sys::KeyboardEvent> {
+        let key = key.to_owned();
+        self.until_keydown().with_filter(move |ev| ev.key() == key)
+    }
+    /// Like [until_keydown][EmitElementEvent::until_keydown], but only fires
+    /// for events for which the given function returns true.
+    ///
+    /// Other events are ignored without waking the executor.
+    /// ```
+    /// # use async_ui_web_html::{events::EmitElementEvent, nodes::TextArea};
+    /// # let _ = async {
+    /// # let textarea = TextArea::new();
+    /// textarea.until_keydown_with(|ev| ev.ctrl_key() && ev.key() == "Enter").await;
+    /// # };
+    /// ```
+    #[must_use = "the returned object is a Future+Stream that does nothing unless polled"]
+    fn until_keydown_with(
+        &self,
+        filter: impl Fn(&web_sys::KeyboardEvent) -> bool + 'static,
+    ) -> EventFutureStream<web_sys::KeyboardEvent> {
+        self.until_keydown().with_filter(filter)
+    }
 }
 
 impl EmitElementEvent for Element {}
@@ -0,0 +1,357 @@
+use std::{
+    borrow::Cow,
+    cell::{Cell, OnceCell, RefCell},
+    collections::HashMap,
+    future::Future,
+    rc::Rc,
+    task::Poll,
+};
+
+use futures_core::Stream;
+use wasm_bindgen::{prelude::Closure, JsCast, UnwrapThrowExt};
+use web_sys::{AddEventListenerOptions, Element};
+
+use crate::event_handling::{EventBuffer, Shared};
+
+/// The HTML attribute [EventDelegator] uses to identify elements.
+///
+/// [until_event_on][EventDelegator::until_event_on] sets this attribute
+/// automatically. If you use [until_event_for][EventDelegator::until_event_for],
+/// set the attribute yourself.
+pub const DELEGATE_KEY_ATTRIBUTE: &str = "data-delegate-key";
+
+thread_local! {
+    static NEXT_KEY: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Listen to events on many elements with a single JS listener.
+///
+/// Attaching a listener to every item in a large list takes memory and time.
+/// An `EventDelegator` attaches one listener to a container element instead.
+/// When an event fires inside the container, the delegator looks at the
+/// event target and its ancestors (up to the container), and delivers the
+/// event to the streams registered for those elements.
+///
+/// Items can still `await` their own events.
+///
+/// ```
+/// # use async_ui_web_html::{events::EventDelegator, nodes::{Li, Text, Ul}};
+/// # use async_ui_web_core::combinators::join;
+/// # let _ = async {
+/// let list = Ul::new();
+/// let clicks = EventDelegator::<web_sys::MouseEvent>::new(
+///     list.element.clone().into(),
+///     "click".into(),
+/// );
+/// let item = |i: usize| {
+///     let clicks = &clicks;
+///     async move {
+///         let li = Li::new();
+///         let text = Text::new();
+///         text.set_data(&format!("item {i}"));
+///         join((li.render(text.render()), async {
+///             loop {
+///                 // no JS closure is created for this
+///                 clicks.until_event_on(&li).await;
+///                 text.set_data(&format!("item {i} was clicked"));
+///             }
+///         }))
+///         .await;
+///     }
+/// };
+/// list.render(join((0..10000).map(item).collect::<Vec<_>>())).await;
+/// # };
+/// ```
+///
+/// Events are delivered to the innermost registered element first, then to
+/// registered ancestors.
+pub struct EventDelegator<E> {
+    container: Element,
+    event_name: Cow<'static, str>,
+    options: Option<AddEventListenerOptions>,
+    capture: bool,
+    closure: OnceCell<Closure<dyn Fn(web_sys::Event)>>,
+    routes: Rc<RefCell<Routes<E>>>,
+}
+
+/// The streams listening for each key, identified by their ids.
+type Listeners<E> = Vec<(u64, Rc<RefCell<Shared<E>>>)>;
+
+struct Routes<E> {
+    map: HashMap<String, Listeners<E>>,
+    next_id: u64,
+}
+
+impl<E> Routes<E> {
+    fn new() -> Self {
+        Self {
+            map: HashMap::new(),
+            next_id: 0,
+        }
+    }
+    /// Register a stream for the key. Returns the id to remove it with.
+    fn add(&mut self, key: String, shared: Rc<RefCell<Shared<E>>>) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.map.entry(key).or_default().push((id, shared));
+        id
+    }
+    fn remove(&mut self, key: &str, id: u64) {
+        if let Some(listeners) = self.map.get_mut(key) {
+            listeners.retain(|(i, _)| *i != id);
+            if listeners.is_empty() {
+                self.map.remove(key);
+            }
+        }
+    }
+    /// The streams to deliver an event to, given the keys of the registered
+    /// elements from the event target outward.
+    fn listeners_for(&self, keys: &[String]) -> Vec<Rc<RefCell<Shared<E>>>> {
+        keys.iter()
+            .filter_map(|key| self.map.get(key))
+            .flatten()
+            .map(|(_, shared)| shared.clone())
+            .collect()
+    }
+}
+
+impl<E: JsCast + 'static> EventDelegator<E> {
+    /// Create a delegator for the given event name on the given container.
+    ///
+    /// The JS listener is attached when the first stream is created.
+    pub fn new(container: Element, event_name: Cow<'static, str>) -> Self {
+        Self {
+            container,
+            event_name,
+            options: None,
+            capture: false,
+            closure: OnceCell::new(),
+            routes: Rc::new(RefCell::new(Routes::new())),
+        }
+    }
+    /// Set the `capture` option of the listener.
+    /// This is needed for events that don't bubble, such as `focus`.
+    ///
+    /// See [EventFutureStream::set_capture][crate::events::EventFutureStream::set_capture].
+    ///
+    /// This needs to be set *before* you create the first stream.
+    pub fn set_capture(&mut self, capture: bool) {
+        self.capture = capture;
+        self.options
+            .get_or_insert_with(AddEventListenerOptions::new)
+            .set_capture(capture);
+    }
+    /// Set the `passive` option of the listener.
+    ///
+    /// See [EventFutureStream::set_passive][crate::events::EventFutureStream::set_passive].
+    ///
+    /// This needs to be set *before* you create the first stream.
+    pub fn set_passive(&mut self, passive: bool) {
+        self.options
+            .get_or_insert_with(AddEventListenerOptions::new)
+            .set_passive(passive);
+    }
+    /// Wait for the event to fire on the given element or its descendants.
+    /// The return type is both a [Future] and a [Stream].
+    ///
+    /// The element must be inside the container.
+    /// If it doesn't have a [DELEGATE_KEY_ATTRIBUTE] yet, one is generated.
+    pub fn until_event_on(&self, element: &impl AsRef<Element>) -> DelegatedEventStream<'_, E> {
+        let element = element.as_ref();
+        let key = element
+            .get_attribute(DELEGATE_KEY_ATTRIBUTE)
+            .unwrap_or_else(|| {
+                let key = NEXT_KEY.with(|k| k.replace(k.get() + 1)).to_string();
+                element
+                    .set_attribute(DELEGATE_KEY_ATTRIBUTE, &key)
+                    .unwrap_throw();
+                key
+            });
+        self.until_event_for(key)
+    }
+    /// Wait for the event to fire on any element whose [DELEGATE_KEY_ATTRIBUTE]
+    /// is the given key, or on descendants of such element.
+    /// The return type is both a [Future] and a [Stream].
+    pub fn until_event_for(&self, key: String) -> DelegatedEventStream<'_, E> {
+        self.closure.get_or_init(|| self.attach());
+        let shared = Rc::new(RefCell::new(Shared::new()));
+        let id = self.routes.borrow_mut().add(key.clone(), shared.clone());
+        DelegatedEventStream {
+            routes: &self.routes,
+            key,
+            id,
+            shared,
+        }
+    }
+    fn attach(&self) -> Closure<dyn Fn(web_sys::Event)> {
+        let routes = Rc::downgrade(&self.routes);
+        let container = self.container.clone();
+        let selector = format!("[{DELEGATE_KEY_ATTRIBUTE}]");
+        let closure = Closure::new(move |ev: web_sys::Event| {
+            let Some(routes) = routes.upgrade() else {
+                return;
+            };
+            let mut keys = Vec::new();
+            let mut current = ev
+                .target()
+                .and_then(|t| t.dyn_into::<Element>().ok())
+                .and_then(|t| t.closest(&selector).ok().flatten());
+            while let Some(element) = current {
+                if !container.contains(Some(&element)) {
+                    break;
+                }
+                keys.extend(element.get_attribute(DELEGATE_KEY_ATTRIBUTE));
+                current = element
+                    .parent_element()
+                    .and_then(|p| p.closest(&selector).ok().flatten());
+            }
+            let listeners = routes.borrow().listeners_for(&keys);
+            listeners.iter().for_each(|shared| {
+                shared
+                    .borrow_mut()
+                    .push_and_wake(ev.clone().unchecked_into())
+            });
+            async_ui_web_core::executor::run_now();
+        });
+        let listener = closure.as_ref().unchecked_ref();
+        if let Some(options) = &self.options {
+            self.container
+                .add_event_listener_with_callback_and_add_event_listener_options(
+                    &self.event_name,
+                    listener,
+                    options,
+                )
+                .unwrap_throw();
+        } else {
+            self.container
+                .add_event_listener_with_callback(&self.event_name, listener)
+                .unwrap_throw();
+        }
+        closure
+    }
+}
+
+impl<E> Drop for EventDelegator<E> {
+    fn drop(&mut self) {
+        if let Some(callback) = self.closure.take() {
+            self.container
+                .remove_event_listener_with_callback_and_bool(
+                    &self.event_name,
+                    callback.as_ref().unchecked_ref(),
+                    self.capture,
+                )
+                .unwrap_throw();
+        }
+    }
+}
+
+/// A struct implementing both [Future] and [Stream].
+/// Yields event objects delivered by an [EventDelegator].
+///
+/// Like [EventFutureStream][crate::events::EventFutureStream], this only keeps
+/// the latest event by default. Use [set_buffer][Self::set_buffer] to change that.
+pub struct DelegatedEventStream<'d, E> {
+    routes: &'d RefCell<Routes<E>>,
+    key: String,
+    id: u64,
+    shared: Rc<RefCell<Shared<E>>>,
+}
+
+impl<'d, E> DelegatedEventStream<'d, E> {
+    /// Choose how events that arrive before they are consumed are stored.
+    ///
+    /// See [EventFutureStream::set_buffer][crate::events::EventFutureStream::set_buffer].
+    pub fn set_buffer(&mut self, buffer: EventBuffer<E>) {
+        self.shared.borrow_mut().set_buffer(buffer);
+    }
+}
+
+impl<'d, E> Stream for DelegatedEventStream<'d, E> {
+    type Item = E;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let mut shared = self.shared.borrow_mut();
+        shared.update_waker(cx.waker());
+        match shared.pop() {
+            Some(ev) => Poll::Ready(Some(ev)),
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl<'d, E> Future for DelegatedEventStream<'d, E> {
+    type Output = E;
+
+    fn poll(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        match self.poll_next(cx) {
+            Poll::Ready(Some(ev)) => Poll::Ready(ev),
+            _ => Poll::Pending,
+        }
+    }
+}
+
+impl<'d, E> Drop for DelegatedEventStream<'d, E> {
+    fn drop(&mut self) {
+        self.routes.borrow_mut().remove(&self.key, self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::Routes;
+    use crate::event_handling::Shared;
+
+    fn deliver(routes: &Routes<i32>, keys: &[&str], ev: i32) {
+        let keys: Vec<String> = keys.iter().map(|k| k.to_string()).collect();
+        routes
+            .listeners_for(&keys)
+            .iter()
+            .for_each(|shared| shared.borrow_mut().push_and_wake(ev));
+    }
+
+    #[test]
+    fn routes_by_key() {
+        let mut routes = Routes::new();
+        let a1 = Rc::new(RefCell::new(Shared::new()));
+        let a2 = Rc::new(RefCell::new(Shared::new()));
+        let b = Rc::new(RefCell::new(Shared::new()));
+        let a1_id = routes.add("a".into(), a1.clone());
+        routes.add("a".into(), a2.clone());
+        routes.add("b".into(), b.clone());
+        deliver(&routes, &["a"], 1);
+        assert_eq!(a1.borrow_mut().pop(), Some(1));
+        assert_eq!(a2.borrow_mut().pop(), Some(1));
+        assert_eq!(b.borrow_mut().pop(), None);
+
+        routes.remove("a", a1_id);
+        deliver(&routes, &["a"], 2);
+        assert_eq!(a1.borrow_mut().pop(), None);
+        assert_eq!(a2.borrow_mut().pop(), Some(2));
+        routes.remove("b", 2);
+        assert!(!routes.map.contains_key("b"));
+    }
+    #[test]
+    fn ancestors() {
+        let mut routes = Routes::new();
+        let inner = Rc::new(RefCell::new(Shared::new()));
+        let outer = Rc::new(RefCell::new(Shared::new()));
+        routes.add("inner".into(), inner.clone());
+        routes.add("outer".into(), outer.clone());
+        // an event on the inner element is also delivered to its ancestor, in order
+        let listeners = routes.listeners_for(&["inner".into(), "outer".into()]);
+        assert!(Rc::ptr_eq(&listeners[0], &inner));
+        assert!(Rc::ptr_eq(&listeners[1], &outer));
+        // an event on the outer element only goes to the outer element
+        deliver(&routes, &["outer"], 1);
+        assert_eq!(inner.borrow_mut().pop(), None);
+        assert_eq!(outer.borrow_mut().pop(), Some(1));
+        // elements without listeners are skipped
+        deliver(&routes, &["unregistered", "outer"], 2);
+        assert_eq!(outer.borrow_mut().pop(), Some(2));
+    }
+}
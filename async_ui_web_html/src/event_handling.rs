@@ -1,6 +1,7 @@
 use std::{
     borrow::Cow,
     cell::RefCell,
+    collections::VecDeque,
     future::Future,
     rc::Rc,
     task::{Poll, Waker},
@@ -9,7 +10,7 @@ use std::{
 use async_ui_internal_utils::dummy_waker::dummy_waker;
 use futures_core::Stream;
 use wasm_bindgen::{prelude::Closure, JsCast, UnwrapThrowExt};
-use web_sys::{AddEventListenerOptions, EventTarget};
+use web_sys::{AbortSignal, AddEventListenerOptions, EventTarget};
 
 /// A struct implementing both [Future] and [Stream].
 /// Yields [Event][web_sys::Event] objects.
@@ -17,19 +18,218 @@ use web_sys::{AddEventListenerOptions, EventTarget};
 /// Use [until_event][crate::events::EmitEvent::until_event] or other until_*
 /// methods to get this struct.
 ///
+/// ### Listener Options
+///
+/// Besides the standard `addEventListener` options ([set_capture][Self::set_capture],
+/// [set_passive][Self::set_passive], [set_signal][Self::set_signal]),
+/// you can make the listener itself call
+/// [set_prevent_default][Self::set_prevent_default] or
+/// [set_stop_propagation][Self::set_stop_propagation],
+/// ignore events with [set_filter][Self::set_filter],
+/// and stop after the first event with [set_once][Self::set_once].
+/// These are applied inside the JS listener, before the executor is woken.
+///
 /// ### Notes for the Stream API
 ///
 /// *   The returned Stream is never exhausted.
-/// *   The implementation only keeps the last event it receives.
+/// *   By default, the implementation only keeps the last event it receives.
 ///     This means if you use some custom manually-implemented wrapper futures and
-///     fail to poll the Stream upon `wake`, you might miss some
-///     in-between events.
+///     fail to poll the Stream upon `wake`, or if your loop `await`s something
+///     else between events, you might miss some in-between events.
+///     Use [set_buffer][Self::set_buffer] to keep more events.
 pub struct EventFutureStream<E> {
     target: EventTarget,
     closure: Option<Closure<dyn Fn(web_sys::Event)>>,
-    shared: Rc<RefCell<(Option<E>, Waker)>>,
+    shared: Rc<RefCell<Shared<E>>>,
     options: Option<AddEventListenerOptions>,
+    capture: bool,
     event_name: Cow<'static, str>,
+    listener: Option<Listener<E>>,
+}
+
+type EventFilter<E> = Box<dyn Fn(&E) -> bool>;
+
+/// What the JS listener does with each event it receives,
+/// before handing it to the Rust stream.
+struct Listener<E> {
+    filter: Option<EventFilter<E>>,
+    prevent_default: bool,
+    stop_propagation: bool,
+    once: bool,
+    done: bool,
+}
+
+/// The [Event][web_sys::Event] methods the [Listener] calls.
+trait ListenerEvent {
+    fn prevent_default(&self);
+    fn stop_propagation(&self);
+}
+
+impl<E: JsCast> ListenerEvent for E {
+    fn prevent_default(&self) {
+        self.unchecked_ref::<web_sys::Event>().prevent_default();
+    }
+    fn stop_propagation(&self) {
+        self.unchecked_ref::<web_sys::Event>().stop_propagation();
+    }
+}
+
+impl<E> Default for Listener<E> {
+    fn default() -> Self {
+        Self {
+            filter: None,
+            prevent_default: false,
+            stop_propagation: false,
+            once: false,
+            done: false,
+        }
+    }
+}
+
+impl<E: ListenerEvent> Listener<E> {
+    /// Apply the filter and the options to the event.
+    /// Returns whether the event should be delivered to the stream.
+    fn handle(&mut self, ev: &E) -> bool {
+        if self.done || self.filter.as_ref().is_some_and(|filter| !filter(ev)) {
+            return false;
+        }
+        if self.prevent_default {
+            ev.prevent_default();
+        }
+        if self.stop_propagation {
+            ev.stop_propagation();
+        }
+        self.done = self.once;
+        true
+    }
+}
+
+/// How an [EventFutureStream] stores events that arrive faster than they
+/// are consumed.
+///
+/// Set with [set_buffer][EventFutureStream::set_buffer].
+/// The default is [Latest][EventBuffer::Latest].
+///
+/// ```
+/// # use async_ui_web_html::{events::{EmitElementEvent, EventBuffer}, nodes::Div};
+/// # use futures_lite::StreamExt;
+/// # let _ = async {
+/// # let canvas = Div::new();
+/// let mut moves = canvas.until_mousemove();
+/// // we're drawing a line, so we need every single point
+/// moves.set_buffer(EventBuffer::Unbounded);
+/// while let Some(ev) = moves.next().await {
+///     // slow drawing code here won't cause us to skip points
+/// }
+/// # };
+/// ```
+#[derive(Default)]
+pub enum EventBuffer<E> {
+    /// Keep only the most recent event.
+    /// An event that has not been consumed is replaced by the new one.
+    #[default]
+    Latest,
+    /// Keep every event, without limit.
+    Unbounded,
+    /// Keep at most the given number of events.
+    /// When the queue is full, the oldest queued event is discarded to make
+    /// room for the new one.
+    ///
+    /// The limit must be at least 1.
+    DropOldest(usize),
+    /// Keep at most the given number of events.
+    /// When the queue is full, new events are discarded.
+    ///
+    /// The limit must be at least 1.
+    DropNewest(usize),
+    /// Keep every event, but merge consecutive events.
+    ///
+    /// When a new event arrives, the function is called with the last queued
+    /// event and the new event. If it returns true, the new event replaces the
+    /// queued one. Otherwise, the new event is added to the queue.
+    ///
+    /// For example, to keep every `mousedown` and `mouseup` but only the latest
+    /// of consecutive `mousemove`s, coalesce when both events are `mousemove`.
+    #[allow(clippy::type_complexity)]
+    Coalesce(Box<dyn Fn(&E, &E) -> bool>),
+}
+
+/// Event queue shared between the JS listener closure and the Rust stream.
+pub(crate) struct Shared<E> {
+    queue: VecDeque<E>,
+    buffer: EventBuffer<E>,
+    waker: Waker,
+}
+
+impl<E> Shared<E> {
+    pub(crate) fn new() -> Self {
+        Self {
+            queue: VecDeque::new(),
+            buffer: EventBuffer::Latest,
+            waker: dummy_waker(),
+        }
+    }
+    /// Put a new event in the queue according to the buffering mode,
+    /// then wake the stream.
+    pub(crate) fn push_and_wake(&mut self, ev: E) {
+        self.push(ev);
+        self.waker.wake_by_ref();
+    }
+    /// Put a new event in the queue according to the buffering mode.
+    fn push(&mut self, ev: E) {
+        match &self.buffer {
+            EventBuffer::Latest => {
+                self.queue.clear();
+                self.queue.push_back(ev);
+            }
+            EventBuffer::Unbounded => self.queue.push_back(ev),
+            EventBuffer::DropOldest(limit) => {
+                self.queue.push_back(ev);
+                while self.queue.len() > *limit {
+                    self.queue.pop_front();
+                }
+            }
+            EventBuffer::DropNewest(limit) => {
+                if self.queue.len() < *limit {
+                    self.queue.push_back(ev);
+                }
+            }
+            EventBuffer::Coalesce(should_merge) => match self.queue.back_mut() {
+                Some(last) if should_merge(last, &ev) => *last = ev,
+                _ => self.queue.push_back(ev),
+            },
+        }
+    }
+    pub(crate) fn set_buffer(&mut self, buffer: EventBuffer<E>) {
+        assert!(
+            !matches!(
+                buffer,
+                EventBuffer::DropOldest(0) | EventBuffer::DropNewest(0)
+            ),
+            "an event buffer must be able to hold at least one event"
+        );
+        self.buffer = buffer;
+        let len = self.queue.len();
+        match self.buffer {
+            EventBuffer::Latest if len > 1 => {
+                self.queue.drain(..len - 1);
+            }
+            EventBuffer::DropOldest(limit) if len > limit => {
+                self.queue.drain(..len - limit);
+            }
+            EventBuffer::DropNewest(limit) => self.queue.truncate(limit),
+            _ => {}
+        }
+    }
+    /// Store the waker to be woken when an event arrive.
+    pub(crate) fn update_waker(&mut self, waker: &Waker) {
+        if !self.waker.will_wake(waker) {
+            self.waker = waker.to_owned();
+        }
+    }
+    pub(crate) fn pop(&mut self) -> Option<E> {
+        self.queue.pop_front()
+    }
 }
 
 impl<E: JsCast> EventFutureStream<E> {
@@ -39,9 +239,11 @@ impl<E: JsCast> EventFutureStream<E> {
         Self {
             target,
             closure: None,
-            shared: Rc::new(RefCell::new((None, dummy_waker()))),
+            shared: Rc::new(RefCell::new(Shared::new())),
             options: None,
+            capture: false,
             event_name,
+            listener: Some(Listener::default()),
         }
     }
     /// The `capture` option indicates that that events of this type will be
@@ -53,9 +255,10 @@ impl<E: JsCast> EventFutureStream<E> {
     ///
     /// This needs to be set *before* you first poll the stream.
     pub fn set_capture(&mut self, capture: bool) {
+        self.capture = capture;
         self.options
             .get_or_insert_with(AddEventListenerOptions::new)
-            .capture(capture);
+            .set_capture(capture);
     }
     /// The `passive` option indicates that the function specified by listener
     /// will never call `preventDefault()`.
@@ -70,7 +273,98 @@ impl<E: JsCast> EventFutureStream<E> {
     pub fn set_passive(&mut self, passive: bool) {
         self.options
             .get_or_insert_with(AddEventListenerOptions::new)
-            .passive(passive);
+            .set_passive(passive);
+    }
+    /// If true, the listener only handles the first event it receives
+    /// (that passes the [filter][Self::set_filter]).
+    /// Later events are ignored without waking the executor, and without
+    /// [set_prevent_default][Self::set_prevent_default] or
+    /// [set_stop_propagation][Self::set_stop_propagation] applying to them.
+    ///
+    /// With this option, the Stream yields at most one event.
+    ///
+    /// This is like the `once` option of `addEventListener`, except that
+    /// events rejected by the filter don't count.
+    ///
+    /// This needs to be set *before* you first poll the stream.
+    pub fn set_once(&mut self, once: bool) {
+        self.listener_mut().once = once;
+    }
+    /// The `signal` option lets you remove the listener by calling `abort()`
+    /// on the `AbortController` that owns the signal.
+    /// After that, the Stream no longer receives events.
+    ///
+    /// See [MDN documentation on `addEventListener`](https://developer.mozilla.org/en-US/docs/Web/API/EventTarget/addEventListener).
+    ///
+    /// This needs to be set *before* you first poll the stream.
+    pub fn set_signal(&mut self, signal: &AbortSignal) {
+        self.options
+            .get_or_insert_with(AddEventListenerOptions::new)
+            .set_signal(signal);
+    }
+    /// If true, the listener calls [prevent_default][web_sys::Event::prevent_default]
+    /// on every event it receives (that passes the [filter][Self::set_filter]).
+    ///
+    /// This is done inside the JS listener, so it works even if your Rust code
+    /// doesn't get to run synchronously.
+    ///
+    /// This needs to be set *before* you first poll the stream.
+    pub fn set_prevent_default(&mut self, prevent_default: bool) {
+        self.listener_mut().prevent_default = prevent_default;
+    }
+    /// If true, the listener calls [stop_propagation][web_sys::Event::stop_propagation]
+    /// on every event it receives (that passes the [filter][Self::set_filter]).
+    ///
+    /// This needs to be set *before* you first poll the stream.
+    pub fn set_stop_propagation(&mut self, stop_propagation: bool) {
+        self.listener_mut().stop_propagation = stop_propagation;
+    }
+    /// Only deliver events for which the given function returns true.
+    ///
+    /// Other events are dropped inside the JS listener without waking the
+    /// executor. They are not affected by
+    /// [set_prevent_default][Self::set_prevent_default] or
+    /// [set_stop_propagation][Self::set_stop_propagation] either.
+    ///
+    /// ```
+    /// # use async_ui_web_html::{events::EmitElementEvent, nodes::Div};
+    /// # let _ = async {
+    /// # let div = Div::new();
+    /// let mut right_clicks = div.until_mousedown();
+    /// right_clicks.set_filter(|ev| ev.button() == 2);
+    /// right_clicks.await;
+    /// # };
+    /// ```
+    ///
+    /// This needs to be set *before* you first poll the stream.
+    pub fn set_filter(&mut self, filter: impl Fn(&E) -> bool + 'static) {
+        self.listener_mut().filter = Some(Box::new(filter));
+    }
+    /// Like [set_filter][Self::set_filter], but takes and returns `self`
+    /// for convenient chaining.
+    pub fn with_filter(mut self, filter: impl Fn(&E) -> bool + 'static) -> Self {
+        self.set_filter(filter);
+        self
+    }
+    /// Choose how events that arrive before they are consumed are stored.
+    /// See [EventBuffer] for the available modes.
+    ///
+    /// By default, only the latest event is kept.
+    ///
+    /// This can be called at any time. Events already queued are kept,
+    /// but will be discarded by the new mode if they don't fit.
+    ///
+    /// # Panics
+    /// Panics if given [DropOldest(0)][EventBuffer::DropOldest] or
+    /// [DropNewest(0)][EventBuffer::DropNewest], which would discard every event.
+    pub fn set_buffer(&mut self, buffer: EventBuffer<E>) {
+        self.shared.borrow_mut().set_buffer(buffer);
+    }
+    /// The listener options, which move into the JS closure on first poll.
+    fn listener_mut(&mut self) -> &mut Listener<E> {
+        self.listener
+            .as_mut()
+            .expect_throw("listener options must be set before the stream is first polled")
     }
 }
 
@@ -93,21 +387,17 @@ impl<E: JsCast + 'static> Stream for EventFutureStream<E> {
     ) -> Poll<Option<Self::Item>> {
         let this = self.get_mut();
 
-        {
-            let shared = &mut *this.shared.borrow_mut();
-            let waker = cx.waker();
-            if !shared.1.will_wake(waker) {
-                shared.1 = waker.to_owned();
-            }
-        }
+        this.shared.borrow_mut().update_waker(cx.waker());
 
         if this.closure.is_none() {
             let shared_weak = Rc::downgrade(&this.shared);
+            let listener = RefCell::new(this.listener.take().unwrap_or_default());
             let closure = Closure::new(move |ev: web_sys::Event| {
+                if !listener.borrow_mut().handle(ev.unchecked_ref::<E>()) {
+                    return;
+                }
                 if let Some(strong) = shared_weak.upgrade() {
-                    let inner = &mut *strong.borrow_mut();
-                    inner.0 = Some(ev.unchecked_into());
-                    inner.1.wake_by_ref();
+                    strong.borrow_mut().push_and_wake(ev.unchecked_into());
                 }
                 async_ui_web_core::executor::run_now();
             });
@@ -127,7 +417,7 @@ impl<E: JsCast + 'static> Stream for EventFutureStream<E> {
             }
             this.closure = Some(closure);
             Poll::Pending
-        } else if let Some(ev) = this.shared.borrow_mut().0.take() {
+        } else if let Some(ev) = this.shared.borrow_mut().pop() {
             Poll::Ready(Some(ev))
         } else {
             Poll::Pending
@@ -160,11 +450,121 @@ impl<E> Drop for EventFutureStream<E> {
     fn drop(&mut self) {
         if let Some(callback) = self.closure.take() {
             self.target
-                .remove_event_listener_with_callback(
+                .remove_event_listener_with_callback_and_bool(
                     &self.event_name,
                     callback.as_ref().unchecked_ref(),
+                    self.capture,
                 )
                 .unwrap_throw();
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::{EventBuffer, Listener, ListenerEvent, Shared};
+
+    #[derive(Default)]
+    struct TestEvent {
+        value: i32,
+        default_prevented: Cell<bool>,
+        propagation_stopped: Cell<bool>,
+    }
+    impl ListenerEvent for TestEvent {
+        fn prevent_default(&self) {
+            self.default_prevented.set(true);
+        }
+        fn stop_propagation(&self) {
+            self.propagation_stopped.set(true);
+        }
+    }
+    fn event(value: i32) -> TestEvent {
+        TestEvent {
+            value,
+            ..Default::default()
+        }
+    }
+
+    fn push_all(buffer: EventBuffer<i32>, events: impl IntoIterator<Item = i32>) -> Vec<i32> {
+        let mut shared = Shared::new();
+        shared.buffer = buffer;
+        events.into_iter().for_each(|ev| shared.push(ev));
+        shared.queue.into_iter().collect()
+    }
+
+    #[test]
+    fn latest() {
+        assert_eq!(push_all(EventBuffer::Latest, 1..=5), [5]);
+    }
+    #[test]
+    fn unbounded() {
+        assert_eq!(push_all(EventBuffer::Unbounded, 1..=5), [1, 2, 3, 4, 5]);
+    }
+    #[test]
+    fn drop_oldest() {
+        assert_eq!(push_all(EventBuffer::DropOldest(3), 1..=5), [3, 4, 5]);
+    }
+    #[test]
+    fn drop_newest() {
+        assert_eq!(push_all(EventBuffer::DropNewest(3), 1..=5), [1, 2, 3]);
+    }
+    #[test]
+    fn coalesce() {
+        // merge consecutive odd numbers
+        let buffer = EventBuffer::Coalesce(Box::new(|a: &i32, b: &i32| a % 2 == 1 && b % 2 == 1));
+        assert_eq!(push_all(buffer, [1, 3, 5, 2, 4, 7, 9]), [5, 2, 4, 9]);
+    }
+    #[test]
+    #[should_panic]
+    fn zero_capacity() {
+        Shared::<i32>::new().set_buffer(EventBuffer::DropOldest(0));
+    }
+    #[test]
+    fn filter() {
+        let mut listener = Listener {
+            filter: Some(Box::new(|ev: &TestEvent| ev.value % 2 == 0)),
+            prevent_default: true,
+            ..Default::default()
+        };
+        let (odd, even) = (event(1), event(2));
+        assert!(!listener.handle(&odd));
+        assert!(!odd.default_prevented.get());
+        assert!(listener.handle(&even));
+        assert!(even.default_prevented.get());
+    }
+    #[test]
+    fn prevent_default_and_stop_propagation() {
+        let mut listener = Listener::default();
+        let ev = event(0);
+        assert!(listener.handle(&ev));
+        assert!(!ev.default_prevented.get() && !ev.propagation_stopped.get());
+
+        listener.prevent_default = true;
+        let ev = event(0);
+        assert!(listener.handle(&ev));
+        assert!(ev.default_prevented.get() && !ev.propagation_stopped.get());
+
+        listener.prevent_default = false;
+        listener.stop_propagation = true;
+        let ev = event(0);
+        assert!(listener.handle(&ev));
+        assert!(!ev.default_prevented.get() && ev.propagation_stopped.get());
+    }
+    #[test]
+    fn once() {
+        let mut listener = Listener {
+            filter: Some(Box::new(|ev: &TestEvent| ev.value > 0)),
+            stop_propagation: true,
+            once: true,
+            ..Default::default()
+        };
+        // rejected events don't use up the listener
+        assert!(!listener.handle(&event(0)));
+        assert!(listener.handle(&event(1)));
+        let ev = event(2);
+        assert!(!listener.handle(&ev));
+        assert!(!ev.propagation_stopped.get());
+    }
+}
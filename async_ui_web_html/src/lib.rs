@@ -1,5 +1,6 @@
 mod common_components;
 mod common_events;
+mod event_delegation;
 mod event_handling;
 mod input_types;
 mod text_node;
@@ -31,7 +32,10 @@ pub mod events {
     */
 
     pub use super::common_events::{EmitElementEvent, EmitHtmlElementEvent};
-    pub use super::event_handling::{EmitEvent, EventFutureStream};
+    pub use super::event_delegation::{
+        DelegatedEventStream, EventDelegator, DELEGATE_KEY_ATTRIBUTE,
+    };
+    pub use super::event_handling::{EmitEvent, EventBuffer, EventFutureStream};
 }
 pub mod nodes {
     /*!
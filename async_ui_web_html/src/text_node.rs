@@ -1,9 +1,12 @@
 use std::{
+    fmt::{Display, Write},
     future::{pending, Pending},
     ops::Deref,
 };
 
 use async_ui_web_core::{window::DOCUMENT, ContainerNodeFuture};
+use futures_lite::{Stream, StreamExt};
+use wasm_bindgen::UnwrapThrowExt;
 
 /// An HTML text node.
 pub struct Text {
@@ -19,6 +22,36 @@ impl Text {
     pub fn render(&self) -> ContainerNodeFuture<Pending<()>> {
         ContainerNodeFuture::new(pending(), self.node.clone().into())
     }
+    /// Set the content of this node to each item of the stream, formatted
+    /// with [Display].
+    ///
+    /// The DOM is only written to when the formatted string changes.
+    /// The returned Future completes when the stream ends.
+    ///
+    /// This does not put the node on the screen; use [render][Self::render] for that.
+    ///
+    /// ```
+    /// # use async_ui_web_html::nodes::Text;
+    /// # use async_ui_web_core::combinators::join;
+    /// # let _ = async {
+    /// # let numbers = futures_lite::stream::iter([1, 2, 3]);
+    /// let text = Text::new();
+    /// join((text.render(), text.bind(numbers))).await;
+    /// # };
+    /// ```
+    pub async fn bind<D: Display>(&self, values: impl Stream<Item = D>) {
+        let mut values = std::pin::pin!(values);
+        let mut current = self.node.data();
+        let mut next = String::new();
+        while let Some(value) = values.next().await {
+            next.clear();
+            write!(next, "{value}").unwrap_throw();
+            if next != current {
+                self.node.set_data(&next);
+                std::mem::swap(&mut current, &mut next);
+            }
+        }
+    }
 }
 
 impl Default for Text {
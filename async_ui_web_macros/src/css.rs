@@ -2,13 +2,45 @@ use std::collections::HashSet;
 
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::{format_ident, quote};
-use syn::{parse_macro_input, LitStr};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input, LitStr, Token,
+};
 
 pub(crate) fn css_macro(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let input = parse_macro_input!(input as LitStr);
-    generate(input.value()).into()
+    let input = parse_macro_input!(input as CssInput);
+    generate(input.css.value(), input.shadow).into()
 }
-fn generate(input: String) -> TokenStream {
+
+/// Either `"css"` or `shadow, "css"`.
+struct CssInput {
+    shadow: bool,
+    css: LitStr,
+}
+
+impl Parse for CssInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let shadow = if input.peek(syn::Ident) {
+            let ident: Ident = input.parse()?;
+            if ident != "shadow" {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "expected `shadow` or a string",
+                ));
+            }
+            input.parse::<Token![,]>()?;
+            true
+        } else {
+            false
+        };
+        Ok(Self {
+            shadow,
+            css: input.parse()?,
+        })
+    }
+}
+
+fn generate(input: String, shadow: bool) -> TokenStream {
     let classes = find_classes::find_classes(&input);
     let postfix = generate_postfix(&input);
     let postfix = std::str::from_utf8(&postfix).unwrap();
@@ -44,22 +76,30 @@ document.head.appendChild(document.createElement(\"style\")).innerHTML = {style_
         "
     );
 
-    quote!(
-    #[doc(hidden)]
-    mod #inner_mod_name {
-        use ::async_ui_web::__private_macro_only::wasm_bindgen;
-        // pub const STYLE_RAW_TEXT: &str = #input;
-        // pub const STYLE_POSTFIXED_TEXT: &str = #output;
-        #[wasm_bindgen::prelude::wasm_bindgen(inline_js = #js_content)]
-        extern "C" {
-            static #style_var_name: wasm_bindgen::JsValue;
-        }
+    let global_injection = (!shadow).then(|| {
+        quote!(
         #[doc(hidden)]
-        #[wasm_bindgen::prelude::wasm_bindgen]
-        pub fn #dce_hack_fn_name() {
-            let _ = &*#style_var_name;
+        mod #inner_mod_name {
+            use ::async_ui_web::__private_macro_only::wasm_bindgen;
+            #[wasm_bindgen::prelude::wasm_bindgen(inline_js = #js_content)]
+            extern "C" {
+                static #style_var_name: wasm_bindgen::JsValue;
+            }
+            #[doc(hidden)]
+            #[wasm_bindgen::prelude::wasm_bindgen]
+            pub fn #dce_hack_fn_name() {
+                let _ = &*#style_var_name;
+            }
         }
-    }
+        )
+    });
+
+    quote!(
+    #global_injection
+    #[doc = "The CSS text, with classnames postfixed."]
+    #[doc = ""]
+    #[doc = "Use this to add the styles to a shadow root."]
+    pub const STYLESHEET: &str = #output;
     #(
         #[allow(non_upper_case_globals)]
         pub const #classes_declaration: &str = #classes_value;
@@ -4,6 +4,10 @@ mod select;
 use select::select_macro;
 
 /// Register CSS to be bundled and generate postfixed classnames.
+///
+/// The CSS text (with postfixed classnames) is also available as a `STYLESHEET` constant.
+/// Write `css!(shadow, "...")` to skip adding the styles to `document.head`,
+/// for styles that are only used in shadow roots.
 #[proc_macro]
 pub fn css(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     css_macro(input)
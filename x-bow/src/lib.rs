@@ -235,6 +235,7 @@ pub mod path_ext_wrappers {
     pub use super::path_ext::{
         bind_for_each::BindForEach, for_each::ForEach, signal_stream::SignalStream,
     };
+    pub use super::until_change::UntilChange;
 }
 
 /// Macro to allows building paths to fields inside a struct/enum.